@@ -1,5 +1,12 @@
+use tauri::ipc::Response;
+use tauri::{AppHandle, Emitter};
+
 use crate::error::AppError;
-use crate::types::{CellValue, FileData, OperationResult, SearchResult, SearchScope};
+use crate::types::{
+    AggregateFunc, CellPosition, CellValue, ColumnProfile, CsvBooleans, FileData, FileFormat,
+    ImportResult, IndexStats, Issue, NewlineStyle, OperationApplied, OperationResult, ReadOptions, RowCount, RowRemoval, SaveResult, SearchMode, SearchResult, SearchScope,
+    SheetData, SheetMatchCount, Tokenizer, ValidationRule, ValueCount, WriteOptions,
+};
 
 /// 全局编辑器状态（使用 Arc<RwLock> 支持多线程访问）
 static EDITOR_STATE: std::sync::OnceLock<std::sync::Arc<std::sync::RwLock<Option<crate::state::editor_state::EditorState>>>> = std::sync::OnceLock::new();
@@ -8,24 +15,119 @@ pub fn get_state() -> std::sync::Arc<std::sync::RwLock<Option<crate::state::edit
     EDITOR_STATE.get_or_init(|| std::sync::Arc::new(std::sync::RwLock::new(None))).clone()
 }
 
+/// 每次执行/撤销/重做后发出的事件，负载为 OperationApplied（供其他窗口同步状态）
+pub const OPERATION_APPLIED_EVENT: &str = "operation-applied";
+
+/// 注册状态变更监听器，将每个操作结果以 "operation-applied" 事件广播给所有窗口
+pub fn init_operation_events(app: &AppHandle) {
+    let app = app.clone();
+    crate::state::editor_state::set_operation_listener(move |action, result| {
+        let payload = OperationApplied { action, kind: result.kind(), result };
+        let _ = app.emit(OPERATION_APPLIED_EVENT, payload);
+    });
+}
+
 // ==================== File Operations ====================
 
-/// 读取文件
+/// 读取文件（通过 "read-progress" 事件上报进度）
+/// 解析在阻塞任务中执行，不占用异步运行时的线程，读取期间 IPC（如 cancel_read）仍能及时处理；
+/// 解析完成后才安装编辑器状态。返回的 FileData 直接从编辑器状态序列化，不额外克隆整个文件数据
 #[tauri::command]
-pub fn read_file(path: String) -> Result<FileData, AppError> {
-    crate::io::file_ops::do_read_file(path)
+pub async fn read_file(app: AppHandle, path: String, options: Option<ReadOptions>) -> Result<Response, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::io::file_ops::do_read_file(&app, path, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .map(Response::new)
 }
 
-/// 保存文件
+/// 读取定宽文本文件（无分隔符，按 widths 切分每行并去除首尾空白），生成单个 sheet
+#[tauri::command(async)]
+pub fn read_fixed_width(
+    app: AppHandle,
+    path: String,
+    widths: Vec<usize>,
+    options: Option<ReadOptions>,
+) -> Result<Response, AppError> {
+    crate::io::file_ops::do_read_fixed_width(&app, path, widths, options.unwrap_or_default()).map(Response::new)
+}
+
+/// 将目录中匹配通配符 pattern（默认 *.csv）的文件作为新 sheet 加入当前工作簿（一次撤销即可移除）
+/// 读取失败的文件会被跳过并在 warnings 中报告
+#[tauri::command(async)]
+pub fn import_directory(
+    app: AppHandle,
+    dir: String,
+    pattern: Option<String>,
+    options: Option<ReadOptions>,
+) -> Result<ImportResult, AppError> {
+    crate::io::file_ops::do_import_directory(&app, dir, pattern, options.unwrap_or_default())
+}
+
+/// 读取多个文件并合并为一个工作簿（每个文件的 sheet 作为独立的标签页）
+#[tauri::command(async)]
+pub fn read_files(app: AppHandle, paths: Vec<String>, options: Option<ReadOptions>) -> Result<Response, AppError> {
+    crate::io::file_ops::do_read_files(&app, paths, options.unwrap_or_default()).map(Response::new)
+}
+
+/// 从内存读取文件（如拖入窗口的文件），格式由 file_name 的扩展名决定
+#[tauri::command(async)]
+pub fn read_file_bytes(file_name: String, bytes: Vec<u8>, options: Option<ReadOptions>) -> Result<Response, AppError> {
+    crate::io::file_ops::do_read_file_bytes(file_name, bytes, options.unwrap_or_default()).map(Response::new)
+}
+
+/// 将当前文件数据导出为指定格式的字节（前端收到 ArrayBuffer）
+#[tauri::command(async)]
+pub fn export_bytes(format: FileFormat, options: Option<WriteOptions>) -> Result<Response, AppError> {
+    crate::io::file_ops::do_export_bytes(format, options.unwrap_or_default()).map(Response::new)
+}
+
+/// 分页读取大 CSV 文件的一段行（流式读取，不加载整个文件；分隔符为空时自动检测）
+#[tauri::command(async)]
+pub fn read_csv_rows(
+    path: String,
+    start_row: usize,
+    row_count: usize,
+    delimiter: Option<char>,
+    csv_booleans: Option<CsvBooleans>,
+) -> Result<Vec<Vec<CellValue>>, AppError> {
+    crate::io::file_ops::do_read_csv_rows(path, start_row, row_count, delimiter, csv_booleans.unwrap_or_default())
+}
+
+/// 只读取 xlsx 中定义名称（或表格）引用的区域，名称不区分大小写
+#[tauri::command(async)]
+pub fn read_named_range(path: String, name: String) -> Result<SheetData, AppError> {
+    crate::io::file_ops::do_read_named_range(path, name)
+}
+
+/// 取消正在进行的读取
+#[tauri::command]
+pub fn cancel_read() {
+    crate::io::file_ops::do_cancel_read()
+}
+
+/// 保存文件（通过 "write-progress" 事件上报进度，返回实际保存路径和提示信息）
+/// 写入在阻塞任务中执行，不阻塞 IPC；完成后发出 "save-complete" 或 "save-error" 事件
 #[tauri::command]
-pub fn save_file(path: String, file_data: FileData) -> Result<(), AppError> {
-    crate::io::file_ops::do_save_file(path, file_data)
+pub async fn save_file(
+    app: AppHandle,
+    path: String,
+    file_data: FileData,
+    options: Option<WriteOptions>,
+) -> Result<SaveResult, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::io::file_ops::do_save_file(&app, path, file_data, options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
 }
 
-/// 获取默认保存路径
+/// 获取默认保存路径（绝对路径，优先使用源文件所在目录）
+/// template 支持 {name}、{ext}、{date} 占位符，默认为 "{name}_edited.{ext}"
 #[tauri::command]
-pub fn get_default_save_path(file_name: String) -> String {
-    crate::io::file_ops::do_get_default_save_path(file_name)
+pub fn get_default_save_path(file_name: String, source_path: Option<String>, template: Option<String>) -> String {
+    crate::io::file_ops::do_get_default_save_path(file_name, source_path, template)
 }
 
 /// 初始化文件（用于新建文件时初始化编辑器状态）
@@ -34,17 +136,37 @@ pub fn init_file(file_data: FileData) -> Result<(), AppError> {
     crate::io::file_ops::do_init_file(file_data)
 }
 
+/// 新建空白工作簿（清空当前编辑器状态和撤销历史），返回新的文件数据
+#[tauri::command]
+pub fn new_file(rows: usize, cols: usize, sheet_name: Option<String>) -> Result<FileData, AppError> {
+    crate::io::file_ops::do_new_file(rows, cols, sheet_name)
+}
+
 // ==================== Editor Operations ====================
 
-/// 获取当前文件数据
+/// 获取当前文件数据（后端内存中的权威数据，撤销/重做后可用于前端整体同步）
 #[tauri::command]
 pub fn get_file_data() -> Result<FileData, AppError> {
-    let state = get_state();
-    let guard = state.read().unwrap();
-    match guard.as_ref() {
-        Some(editor_state) => Ok(editor_state.file_data.clone()),
-        None => Err(AppError::Internal("No file loaded".to_string())),
-    }
+    crate::ops::editor_ops::do_get_file_data(get_state())
+}
+
+/// 获取紧凑编码的文件数据（连续空值按游程编码），适合稀疏的大 sheet，前端用 decodeCompactFileData 解码
+/// 小文件直接使用 get_file_data 即可
+#[tauri::command]
+pub fn get_file_data_compact() -> Result<Response, AppError> {
+    crate::ops::editor_ops::do_get_file_data_compact(get_state()).map(Response::new)
+}
+
+/// 当前文件内容的哈希（十六进制字符串），可用于比较两次内容是否一致
+#[tauri::command]
+pub fn content_hash() -> Result<String, AppError> {
+    crate::ops::editor_ops::do_content_hash(get_state())
+}
+
+/// 按内容判断是否有未保存的修改（修改后撤销回原样时为 false）
+#[tauri::command]
+pub fn is_dirty() -> Result<bool, AppError> {
+    crate::ops::editor_ops::do_is_dirty(get_state())
 }
 
 /// 获取编辑器状态（包含能否撤销/重做）
@@ -53,6 +175,31 @@ pub fn get_editor_state() -> Result<Option<crate::state::state::EditorStateInfo>
     crate::ops::editor_ops::do_get_editor_state(get_state())
 }
 
+/// 设置是否同步重建索引（开启后编辑命令返回时搜索结果即反映最新数据）
+#[tauri::command]
+pub fn set_sync_index(enabled: bool) -> Result<(), AppError> {
+    crate::ops::editor_ops::do_set_sync_index(get_state(), enabled)
+}
+
+/// 设置按词、短语搜索使用的分词方式（默认按空白和标点拆分）
+#[tauri::command]
+pub fn set_tokenizer(tokenizer: Tokenizer) -> Result<(), AppError> {
+    crate::ops::editor_ops::do_set_tokenizer(get_state(), tokenizer)
+}
+
+/// 将本次会话的操作历史导出为 JSON 文件，返回导出的操作数
+#[tauri::command]
+pub fn export_operations(path: String) -> Result<usize, AppError> {
+    crate::ops::editor_ops::do_export_operations(get_state(), path)
+}
+
+/// 从 JSON 文件重放操作到当前数据上（每个操作可单独撤销），返回重放的操作数
+/// 完成后需调用 get_file_data 同步前端数据
+#[tauri::command]
+pub fn import_operations(path: String) -> Result<usize, AppError> {
+    crate::ops::editor_ops::do_import_operations(get_state(), path)
+}
+
 /// 撤销操作
 #[tauri::command]
 pub fn undo() -> Result<OperationResult, AppError> {
@@ -67,7 +214,31 @@ pub fn redo() -> Result<OperationResult, AppError> {
 
 // ==================== Cell Operations ====================
 
-/// 设置单元格值
+/// 获取单元格值
+#[tauri::command]
+pub fn get_cell(sheet_index: usize, row: usize, col: usize) -> Result<CellValue, AppError> {
+    crate::ops::cell_ops::do_get_cell(get_state(), sheet_index, row, col)
+}
+
+/// 获取单元格的公式表达式（编辑栏使用），非公式单元格返回 null
+#[tauri::command]
+pub fn get_cell_formula(sheet_index: usize, row: usize, col: usize) -> Result<Option<String>, AppError> {
+    crate::ops::cell_ops::do_get_cell_formula(get_state(), sheet_index, row, col)
+}
+
+/// 按 A1 引用（如 "B12"）获取单元格值，引用格式错误时返回 INVALID_REFERENCE
+#[tauri::command]
+pub fn get_cell_a1(sheet_index: usize, reference: String) -> Result<CellValue, AppError> {
+    crate::ops::cell_ops::do_get_cell_a1(get_state(), sheet_index, reference)
+}
+
+/// 按 A1 区域引用（如 "A1:C3"）获取矩形区域的单元格值（按行）
+#[tauri::command]
+pub fn get_range_a1(sheet_index: usize, reference: String) -> Result<Vec<Vec<CellValue>>, AppError> {
+    crate::ops::cell_ops::do_get_range_a1(get_state(), sheet_index, reference)
+}
+
+/// 设置单元格值，返回是否产生了可撤销的修改（新值与当前值相同时为 false，不记录历史）
 #[tauri::command]
 pub fn set_cell(
     sheet_index: usize,
@@ -75,7 +246,7 @@ pub fn set_cell(
     col: usize,
     old_value: CellValue,
     new_value: CellValue,
-) -> Result<(), AppError> {
+) -> Result<bool, AppError> {
     crate::ops::cell_ops::do_set_cell(get_state(), sheet_index, row, col, old_value, new_value)
 }
 
@@ -85,6 +256,30 @@ pub fn add_row(sheet_index: usize, row_index: usize) -> Result<(), AppError> {
     crate::ops::cell_ops::do_add_row(get_state(), sheet_index, row_index)
 }
 
+/// 在指定行上方插入空行（空 sheet 时插入单格的行），返回操作结果
+#[tauri::command]
+pub fn insert_row_above(sheet_index: usize, row: usize) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_insert_row(get_state(), sheet_index, row, false)
+}
+
+/// 在指定行下方插入空行（空 sheet 时插入单格的行），返回操作结果
+#[tauri::command]
+pub fn insert_row_below(sheet_index: usize, row: usize) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_insert_row(get_state(), sheet_index, row, true)
+}
+
+/// 在行的下方插入该行的副本，一次撤销即可移除
+#[tauri::command]
+pub fn duplicate_row(sheet_index: usize, row_index: usize) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_duplicate_row(get_state(), sheet_index, row_index)
+}
+
+/// 添加带数据的行（按列数补齐或截断）
+#[tauri::command]
+pub fn add_row_with_values(sheet_index: usize, row_index: usize, values: Vec<CellValue>) -> Result<(), AppError> {
+    crate::ops::cell_ops::do_add_row_with_values(get_state(), sheet_index, row_index, values)
+}
+
 /// 删除行
 #[tauri::command]
 pub fn delete_row(sheet_index: usize, row_index: usize) -> Result<(), AppError> {
@@ -97,26 +292,151 @@ pub fn add_column(sheet_index: usize) -> Result<(), AppError> {
     crate::ops::cell_ops::do_add_column(get_state(), sheet_index)
 }
 
+/// 在列的右侧插入该列的副本，一次撤销即可移除
+#[tauri::command]
+pub fn duplicate_column(sheet_index: usize, col_index: usize) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_duplicate_column(get_state(), sheet_index, col_index)
+}
+
 /// 删除列
 #[tauri::command]
 pub fn delete_column(sheet_index: usize, col_index: usize) -> Result<(), AppError> {
     crate::ops::cell_ops::do_delete_column(get_state(), sheet_index, col_index)
 }
 
+/// 移动行（to 为移动后的位置，超出末尾时移到最后），移动没有效果时返回 null
+#[tauri::command]
+pub fn move_row(sheet_index: usize, from: usize, to: usize) -> Result<Option<OperationResult>, AppError> {
+    crate::ops::cell_ops::do_move_row(get_state(), sheet_index, from, to)
+}
+
+/// 移动列（to 为移动后的位置，超出末尾时移到最后），移动没有效果时返回 null
+#[tauri::command]
+pub fn move_column(sheet_index: usize, from: usize, to: usize) -> Result<Option<OperationResult>, AppError> {
+    crate::ops::cell_ops::do_move_column(get_state(), sheet_index, from, to)
+}
+
 // ==================== Sheet Operations ====================
 
-/// 添加 Sheet
+/// 添加 Sheet（使用默认行列数，返回新 sheet）
 #[tauri::command]
-pub fn add_sheet() -> Result<(), AppError> {
+pub fn add_sheet() -> Result<OperationResult, AppError> {
     crate::ops::cell_ops::do_add_sheet(get_state())
 }
 
+/// 添加指定行列数的 Sheet
+#[tauri::command]
+pub fn add_sheet_sized(rows: usize, cols: usize) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_add_sheet_sized(get_state(), Some((rows, cols)))
+}
+
+/// 添加指定名称的 Sheet（名称为空或重名时返回 INVALID_SHEET_NAME）
+#[tauri::command]
+pub fn add_sheet_named(name: String) -> Result<OperationResult, AppError> {
+    crate::ops::cell_ops::do_add_sheet_named(get_state(), name)
+}
+
+/// 设置新建 Sheet 的默认行列数（未设置时为 5×5）
+#[tauri::command]
+pub fn set_default_sheet_size(rows: usize, cols: usize) -> Result<(), AppError> {
+    crate::ops::cell_ops::do_set_default_sheet_size(get_state(), rows, cols)
+}
+
+/// 设置添加行/列时新单元格的值（未设置时为 null）
+#[tauri::command]
+pub fn set_default_cell(value: CellValue) -> Result<(), AppError> {
+    crate::ops::cell_ops::do_set_default_cell(get_state(), value)
+}
+
+/// 设置 sheet 大小上限（新建文件/sheet 和增加行列时校验）
+#[tauri::command]
+pub fn set_size_limits(max_rows: usize, max_cols: usize, max_cells: usize) -> Result<(), AppError> {
+    crate::ops::bounds::set_size_limits(max_rows, max_cols, max_cells)
+}
+
 /// 删除 Sheet
 #[tauri::command]
 pub fn delete_sheet(sheet_index: usize) -> Result<(), AppError> {
     crate::ops::cell_ops::do_delete_sheet(get_state(), sheet_index)
 }
 
+/// 移动 Sheet（to 为移动后的位置，超出末尾时移到最后），移动没有效果时返回 null
+#[tauri::command]
+pub fn move_sheet(from: usize, to: usize) -> Result<Option<OperationResult>, AppError> {
+    crate::ops::cell_ops::do_move_sheet(get_state(), from, to)
+}
+
+/// 将 sheet 调整为指定行列数（截断或用空值补齐），作为一个可撤销的操作
+#[tauri::command]
+pub fn resize_sheet(sheet_index: usize, rows: usize, cols: usize) -> Result<OperationResult, AppError> {
+    crate::ops::sheet_ops::do_resize_sheet(get_state(), sheet_index, rows, cols)
+}
+
+/// 删除重复行（cols 为空或省略时比较整行），preview 为 true 时只返回将被删除的行号
+#[tauri::command]
+pub fn remove_duplicates(sheet_index: usize, cols: Option<Vec<usize>>, preview: Option<bool>) -> Result<RowRemoval, AppError> {
+    crate::ops::sheet_ops::do_remove_duplicates(get_state(), sheet_index, cols.unwrap_or_default(), preview.unwrap_or(false))
+}
+
+/// 删除空行，preview 为 true 时只返回将被删除的行号
+#[tauri::command]
+pub fn remove_empty_rows(sheet_index: usize, preview: Option<bool>) -> Result<RowRemoval, AppError> {
+    crate::ops::sheet_ops::do_remove_empty_rows(get_state(), sheet_index, preview.unwrap_or(false))
+}
+
+/// 按名称查找 sheet 索引（不区分大小写），便于脚本按名称调用其他命令
+#[tauri::command]
+pub fn resolve_sheet(name: String) -> Result<usize, AppError> {
+    crate::ops::sheet_ops::do_resolve_sheet(get_state(), name)
+}
+
+/// 设置 sheet 的第一行是否为表头（排序、分组、透视、查重时固定表头）
+#[tauri::command]
+pub fn set_has_header(sheet_index: usize, has_header: bool) -> Result<(), AppError> {
+    crate::ops::sheet_ops::do_set_has_header(get_state(), sheet_index, has_header)
+}
+
+/// 冻结顶部 rows 行和左侧 cols 列（保存为 xlsx 时应用）
+#[tauri::command]
+pub fn set_freeze(sheet_index: usize, rows: u32, cols: u16) -> Result<(), AppError> {
+    crate::ops::sheet_ops::do_set_freeze(get_state(), sheet_index, rows, cols)
+}
+
+/// 取消冻结窗格
+#[tauri::command]
+pub fn clear_freeze(sheet_index: usize) -> Result<(), AppError> {
+    crate::ops::sheet_ops::do_set_freeze(get_state(), sheet_index, 0, 0)
+}
+
+/// 按列的不同值将 sheet 拆分为多个 sheet（一次撤销即可还原）
+#[tauri::command]
+pub fn split_sheet_by_column(sheet_index: usize, col: usize) -> Result<OperationResult, AppError> {
+    crate::ops::sheet_ops::do_split_sheet_by_column(get_state(), sheet_index, col)
+}
+
+/// 按列分组聚合，结果生成新的 sheet
+#[tauri::command]
+pub fn group_by(
+    sheet_index: usize,
+    key_col: usize,
+    value_col: usize,
+    func: AggregateFunc,
+) -> Result<OperationResult, AppError> {
+    crate::ops::sheet_ops::do_group_by(get_state(), sheet_index, key_col, value_col, func)
+}
+
+/// 生成透视表到新的 sheet
+#[tauri::command]
+pub fn pivot(
+    sheet_index: usize,
+    row_col: usize,
+    col_col: usize,
+    value_col: usize,
+    func: AggregateFunc,
+) -> Result<OperationResult, AppError> {
+    crate::ops::sheet_ops::do_pivot(get_state(), sheet_index, row_col, col_col, value_col, func)
+}
+
 // ==================== Sort Operations ====================
 
 use crate::types::SortState;
@@ -134,12 +454,164 @@ pub fn sort_column(
 
 // ==================== Search Operations ====================
 
-/// 搜索单元格
+/// 搜索单元格（mode 默认为整格匹配）
 #[tauri::command]
 pub fn search(
     query: String,
     scope: SearchScope,
     current_sheet_index: Option<usize>,
+    mode: Option<SearchMode>,
 ) -> Result<Vec<SearchResult>, AppError> {
-    crate::ops::search_ops::do_search(get_state(), query, scope, current_sheet_index)
+    crate::ops::search_ops::do_search(get_state(), query, scope, current_sheet_index, mode.unwrap_or_default())
+}
+
+/// 开始查找并保存匹配列表（供 search_next/search_prev 使用），返回匹配数
+#[tauri::command]
+pub fn search_begin(
+    query: String,
+    scope: SearchScope,
+    current_sheet_index: Option<usize>,
+    mode: Option<SearchMode>,
+) -> Result<usize, AppError> {
+    crate::ops::search_ops::do_search_begin(get_state(), query, scope, current_sheet_index, mode.unwrap_or_default())
+}
+
+/// 跳到下一个匹配（到末尾后回到第一个），没有匹配时返回 null
+#[tauri::command]
+pub fn search_next() -> Result<Option<SearchResult>, AppError> {
+    crate::ops::search_ops::do_search_step(get_state(), true)
+}
+
+/// 跳到上一个匹配（到开头后回到最后一个），没有匹配时返回 null
+#[tauri::command]
+pub fn search_prev() -> Result<Option<SearchResult>, AppError> {
+    crate::ops::search_ops::do_search_step(get_state(), false)
+}
+
+/// 结束查找，清除保存的匹配列表
+#[tauri::command]
+pub fn search_end() -> Result<(), AppError> {
+    crate::ops::search_ops::do_search_end(get_state())
+}
+
+/// 全部替换（整格匹配，可选限定单列），返回修改的单元格数
+#[tauri::command]
+pub fn replace_all(
+    sheet_index: usize,
+    find: String,
+    replace: CellValue,
+    col: Option<usize>,
+) -> Result<usize, AppError> {
+    crate::ops::replace_ops::do_replace_all(get_state(), sheet_index, find, replace, col)
+}
+
+/// 在矩形区域内替换（整格匹配，起止行列均包含），返回修改的单元格数
+#[tauri::command]
+pub fn replace_in_range(
+    sheet_index: usize,
+    start_row: usize,
+    start_col: usize,
+    end_row: usize,
+    end_col: usize,
+    find: String,
+    replace: CellValue,
+) -> Result<usize, AppError> {
+    crate::ops::replace_ops::do_replace_in_range(
+        get_state(),
+        sheet_index,
+        start_row,
+        start_col,
+        end_row,
+        end_col,
+        find,
+        replace,
+    )
+}
+
+/// 统一 sheet 中字符串单元格的换行风格（LF 或 CRLF），返回修改的单元格数
+#[tauri::command]
+pub fn normalize_newlines(sheet_index: usize, style: NewlineStyle) -> Result<usize, AppError> {
+    crate::ops::replace_ops::do_normalize_newlines(get_state(), sheet_index, style)
+}
+
+/// 统计每个 sheet 的搜索命中数
+#[tauri::command]
+pub fn search_summary(query: String, mode: Option<SearchMode>) -> Result<Vec<SheetMatchCount>, AppError> {
+    crate::ops::search_ops::do_search_summary(get_state(), query, mode.unwrap_or_default())
+}
+
+/// 列内自动补全：返回以 prefix 开头（不区分大小写）的不重复值，排序后最多 limit 个
+#[tauri::command]
+pub fn suggest(sheet_index: usize, col: usize, prefix: String, limit: usize) -> Result<Vec<String>, AppError> {
+    crate::ops::search_ops::do_suggest(get_state(), sheet_index, col, prefix, limit)
+}
+
+/// 查看 sheet 的整格索引统计（token 数、位置数、估算字节数）
+#[tauri::command]
+pub fn index_stats(sheet_index: usize) -> Result<IndexStats, AppError> {
+    crate::ops::index_ops::do_index_stats(get_state(), sheet_index)
+}
+
+// ==================== Clipboard Operations ====================
+
+/// 将选区导出为 TSV 文本（前端写入系统剪贴板）
+#[tauri::command]
+pub fn copy_range_tsv(
+    sheet_index: usize,
+    start_row: usize,
+    start_col: usize,
+    end_row: usize,
+    end_col: usize,
+) -> Result<String, AppError> {
+    crate::ops::clipboard_ops::do_copy_range_tsv(get_state(), sheet_index, start_row, start_col, end_row, end_col)
+}
+
+// ==================== Validation Operations ====================
+
+/// 按规则校验列，返回不符合规则的单元格
+#[tauri::command]
+pub fn validate_column(
+    sheet_index: usize,
+    col: usize,
+    rule: ValidationRule,
+) -> Result<Vec<CellPosition>, AppError> {
+    crate::ops::validation_ops::do_validate_column(get_state(), sheet_index, col, rule)
+}
+
+/// 查找列中的重复值
+#[tauri::command]
+pub fn find_duplicates(sheet_index: usize, col: usize) -> Result<Vec<Vec<CellPosition>>, AppError> {
+    crate::ops::validation_ops::do_find_duplicates(get_state(), sheet_index, col)
+}
+
+/// 查找空串或纯空白的字符串单元格（看起来为空但不是 Null）
+#[tauri::command]
+pub fn find_blank_like(sheet_index: usize) -> Result<Vec<CellPosition>, AppError> {
+    crate::ops::validation_ops::do_find_blank_like(get_state(), sheet_index)
+}
+
+/// 导出前检查整个工作簿，返回发现的问题（不规则行、非有限数字、超长文本、重名 sheet、超出 xlsx 上限）
+#[tauri::command]
+pub fn validate_workbook() -> Result<Vec<Issue>, AppError> {
+    crate::ops::validation_ops::do_validate_workbook(get_state())
+}
+
+// ==================== Analysis Operations ====================
+
+/// 统计每列的填充率和类型分布
+#[tauri::command]
+pub fn profile_sheet(sheet_index: usize) -> Result<Vec<ColumnProfile>, AppError> {
+    crate::ops::analysis_ops::do_profile_sheet(get_state(), sheet_index)
+}
+
+/// 统计列中的不重复值及出现次数（按首次出现顺序），include_null 默认为 false
+#[tauri::command]
+pub fn distinct_values(sheet_index: usize, col: usize, include_null: Option<bool>) -> Result<Vec<ValueCount>, AppError> {
+    crate::ops::analysis_ops::do_distinct_values(get_state(), sheet_index, col, include_null.unwrap_or(false))
+}
+
+/// 统计每个 sheet 的总行数和全为空值的行数
+#[tauri::command]
+pub fn row_counts() -> Result<Vec<RowCount>, AppError> {
+    crate::ops::analysis_ops::do_row_counts(get_state())
 }
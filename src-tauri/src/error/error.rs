@@ -1,3 +1,4 @@
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -11,13 +12,46 @@ pub enum AppError {
     UnsupportedFormat,
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("{what} index {index} out of range (valid range: 0..{len})")]
+    OutOfRange {
+        what: &'static str,
+        index: usize,
+        len: usize,
+    },
+    #[error("Invalid sheet name: {0}")]
+    InvalidSheetName(String),
+    #[error("Invalid cell reference: {0}")]
+    InvalidReference(String),
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
 }
 
+impl AppError {
+    /// Machine-readable error code, stable across message wording changes
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::ReadError(_) => "READ_ERROR",
+            AppError::WriteError(_) => "WRITE_ERROR",
+            AppError::UnsupportedFormat => "UNSUPPORTED_FORMAT",
+            AppError::Internal(_) => "INTERNAL",
+            AppError::OutOfRange { .. } => "OUT_OF_RANGE",
+            AppError::InvalidSheetName(_) => "INVALID_SHEET_NAME",
+            AppError::InvalidReference(_) => "INVALID_REFERENCE",
+            AppError::InvalidPattern(_) => "INVALID_PATTERN",
+        }
+    }
+}
+
+/// Serialized as `{ "code": "...", "message": "..." }` so the frontend can
+/// branch on `code` and show `message` to the user
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
     }
 }
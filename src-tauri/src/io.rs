@@ -1,3 +1,5 @@
 pub mod reader;
 pub mod writer;
 pub mod file_ops;
+pub mod progress;
+pub mod number_format;
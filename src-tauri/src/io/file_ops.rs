@@ -1,17 +1,314 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use tauri::{AppHandle, Emitter};
+
 use crate::error::AppError;
-use crate::ops::index_ops::spawn_rebuild_all_sheets_index;
-use crate::state::editor_state::EditorState;
-use crate::types::FileData;
+use crate::ops::bounds::check_sheet_size;
+use crate::ops::index_ops::rebuild_sheet_index;
+use crate::ops::naming::{sanitize_sheet_name, unique_sheet_name, validate_sheet_name};
+use crate::state::editor_state::{EditorState, Operation};
+use crate::types::{
+    CellValue, CsvBooleans, FileData, FileFormat, ImportResult, ReadOptions, SaveResult, SheetData, WriteOptions,
+};
+
+/// 读取进度事件（payload 为 0-100 百分比）
+pub const READ_PROGRESS_EVENT: &str = "read-progress";
+/// 写入进度事件（payload 为 0-100 百分比）
+pub const WRITE_PROGRESS_EVENT: &str = "write-progress";
+/// 保存成功事件（payload 为 SaveResult）
+pub const SAVE_COMPLETE_EVENT: &str = "save-complete";
+/// 保存失败事件（payload 为 AppError）
+pub const SAVE_ERROR_EVENT: &str = "save-error";
+
+/// 新建工作簿的文件名
+const NEW_FILE_NAME: &str = "untitled.xlsx";
+
+/// 读取取消标记（cancel_read 置位，读取循环定期检查）
+static READ_CANCEL: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+fn read_cancel_token() -> Arc<AtomicBool> {
+    READ_CANCEL.get_or_init(|| Arc::new(AtomicBool::new(false))).clone()
+}
 
-/// 读取文件
-pub fn do_read_file(path: String) -> Result<FileData, AppError> {
+/// 读取文件，返回文件数据的 JSON
+/// 读取被取消时返回错误，且不会修改内存中的编辑器状态
+pub fn do_read_file(app: &AppHandle, path: String, options: ReadOptions) -> Result<String, AppError> {
     let path = std::path::Path::new(&path);
-    let file_data = super::reader::read_file(path)?;
+    let cancel = read_cancel_token();
+    cancel.store(false, Ordering::Relaxed);
+    let file_data = super::reader::read_file(
+        path,
+        &options,
+        &|percent| {
+            let _ = app.emit(READ_PROGRESS_EVENT, percent);
+        },
+        &cancel,
+    )?;
 
     // 初始化编辑器状态
-    init_editor_state(file_data.clone());
+    init_editor_state(file_data);
 
-    Ok(file_data)
+    file_data_json()
+}
+
+/// 读取定宽文本文件（按 widths 指定的字符数切分每行，类型推断与 CSV 相同），返回文件数据的 JSON
+/// 读取被取消时返回错误，且不会修改内存中的编辑器状态
+pub fn do_read_fixed_width(
+    app: &AppHandle,
+    path: String,
+    widths: Vec<usize>,
+    options: ReadOptions,
+) -> Result<String, AppError> {
+    let path = std::path::Path::new(&path);
+    let cancel = read_cancel_token();
+    cancel.store(false, Ordering::Relaxed);
+    let file_data = super::reader::read_fixed_width(
+        path,
+        &widths,
+        &options,
+        &|percent| {
+            let _ = app.emit(READ_PROGRESS_EVENT, percent);
+        },
+        &cancel,
+    )?;
+
+    init_editor_state(file_data);
+
+    file_data_json()
+}
+
+/// 读取多个文件并合并为一个工作簿，返回文件数据的 JSON
+/// CSV 的 sheet 以文件名命名，重名的 sheet 追加数字后缀
+pub fn do_read_files(app: &AppHandle, paths: Vec<String>, options: ReadOptions) -> Result<String, AppError> {
+    if paths.is_empty() {
+        return Err(AppError::Internal("No files selected".to_string()));
+    }
+
+    let cancel = read_cancel_token();
+    cancel.store(false, Ordering::Relaxed);
+
+    let file_count = paths.len();
+    let mut combined = FileData {
+        file_name: String::new(),
+        sheets: Vec::new(),
+        delimiter: None,
+        warnings: Vec::new(),
+    };
+
+    for (file_idx, path) in paths.iter().enumerate() {
+        let path = std::path::Path::new(path);
+        let file_data = super::reader::read_file(
+            path,
+            &options,
+            &|percent| {
+                let overall = (file_idx * 100 + percent as usize) / file_count;
+                let _ = app.emit(READ_PROGRESS_EVENT, overall as u8);
+            },
+            &cancel,
+        )?;
+
+        if combined.file_name.is_empty() {
+            combined.file_name = file_data.file_name.clone();
+        }
+        // 记录第一个 CSV 文件使用的分隔符
+        if combined.delimiter.is_none() {
+            combined.delimiter = file_data.delimiter;
+        }
+        combined.warnings.extend(file_data.warnings);
+
+        let is_csv = FileFormat::resolve(options.format, path) == Some(FileFormat::Csv);
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Sheet");
+
+        for mut sheet in file_data.sheets {
+            let base = if is_csv { stem.to_string() } else { sheet.name.clone() };
+            sheet.name = unique_sheet_name(combined.sheets.iter().map(|s| s.name.as_str()), &base);
+            combined.sheets.push(sheet);
+        }
+    }
+
+    // 初始化编辑器状态（索引在首次搜索时构建）
+    init_editor_state(combined);
+
+    file_data_json()
+}
+
+/// 从内存读取文件（如拖入窗口的文件），按 options.format 或 file_name 的扩展名判断格式，返回文件数据的 JSON
+pub fn do_read_file_bytes(file_name: String, bytes: Vec<u8>, options: ReadOptions) -> Result<String, AppError> {
+    // 可识别但无法读取的格式（如 .numbers）由 read_bytes 返回带说明的错误
+    let format = FileFormat::resolve(options.format, std::path::Path::new(&file_name)).ok_or(AppError::UnsupportedFormat)?;
+    let file_data = super::reader::read_bytes(&bytes, format, &file_name, &options)?;
+    drop(bytes);
+
+    init_editor_state(file_data);
+
+    file_data_json()
+}
+
+/// 将当前编辑器中的文件数据导出为指定格式的字节（CSV 只导出第一个 sheet）
+pub fn do_export_bytes(format: FileFormat, options: WriteOptions) -> Result<Vec<u8>, AppError> {
+    let state = crate::commands::get_state();
+    let state_guard = state.read().unwrap();
+    match state_guard.as_ref() {
+        Some(editor_state) => super::writer::write_bytes(&editor_state.file_data, format, &options),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 文件名是否匹配通配符模式（* 匹配任意字符序列，? 匹配单个字符，不区分大小写）
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    // 回溯到最近一个 * 继续匹配
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 将目录中匹配 pattern 的文件（默认 *.csv，按文件名排序）作为新 sheet 加入当前工作簿
+/// CSV 的 sheet 以文件名命名，其它格式沿用原 sheet 名称，重名时追加数字后缀
+/// 读取失败的文件会被跳过并记录在 warnings 中；所有新 sheet 作为一个 Group 操作，一次撤销即可移除
+/// 新 sheet 的索引在返回前构建完成
+pub fn do_import_directory(
+    app: &AppHandle,
+    dir: String,
+    pattern: Option<String>,
+    options: ReadOptions,
+) -> Result<ImportResult, AppError> {
+    let pattern = pattern.unwrap_or_else(|| "*.csv".to_string());
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map_err(|e| AppError::ReadError(e.to_string()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|name| matches_pattern(&pattern, name))
+        })
+        .collect();
+    paths.sort();
+
+    let cancel = read_cancel_token();
+    cancel.store(false, Ordering::Relaxed);
+
+    let file_count = paths.len();
+    let mut warnings = Vec::new();
+    let mut files = Vec::new();
+    for (file_idx, path) in paths.iter().enumerate() {
+        let read = super::reader::read_file(
+            path,
+            &options,
+            &|percent| {
+                let overall = (file_idx * 100 + percent as usize) / file_count;
+                let _ = app.emit(READ_PROGRESS_EVENT, overall as u8);
+            },
+            &cancel,
+        );
+        match read {
+            Ok(file_data) => files.push((path, file_data)),
+            // 取消时放弃整个导入，不修改编辑器状态
+            Err(e) if cancel.load(Ordering::Relaxed) => return Err(e),
+            Err(e) => warnings.push(format!("Skipped '{}': {}", path.display(), e)),
+        }
+    }
+
+    let state = crate::commands::get_state();
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let mut names: Vec<String> = editor_state.file_data.sheets.iter().map(|s| s.name.clone()).collect();
+    let mut operations = Vec::new();
+    for (path, file_data) in files {
+        warnings.extend(file_data.warnings);
+        let is_csv = FileFormat::from_path(path) == Some(FileFormat::Csv);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Sheet");
+
+        for mut sheet in file_data.sheets {
+            let base = if is_csv { sanitize_sheet_name(stem) } else { sheet.name.clone() };
+            sheet.name = unique_sheet_name(names.iter().map(String::as_str), &base);
+            names.push(sheet.name.clone());
+            operations.push(Operation::AddSheet {
+                name: sheet.name.clone(),
+                sheet_data: Some(sheet),
+                sheet_index: None,
+                size: None,
+            });
+        }
+    }
+
+    if operations.is_empty() {
+        return Ok(ImportResult { result: None, warnings });
+    }
+
+    let first_new = editor_state.file_data.sheets.len();
+    let result = editor_state.execute(Operation::Group { operations });
+    let tokenizer = editor_state.tokenizer;
+    for sheet in &mut editor_state.file_data.sheets[first_new..] {
+        rebuild_sheet_index(sheet, tokenizer);
+    }
+
+    Ok(ImportResult { result: Some(result), warnings })
+}
+
+/// 直接从编辑器状态序列化文件数据（读取后数据由编辑器状态持有，避免为返回值再克隆一份）
+fn file_data_json() -> Result<String, AppError> {
+    let state = crate::commands::get_state();
+    let state_guard = state.read().unwrap();
+    match state_guard.as_ref() {
+        Some(editor_state) => {
+            serde_json::to_string(&editor_state.file_data).map_err(|e| AppError::Internal(e.to_string()))
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 分页读取 CSV 文件中的一段行（不加载整个文件，也不修改编辑器状态）
+pub fn do_read_csv_rows(
+    path: String,
+    start_row: usize,
+    row_count: usize,
+    delimiter: Option<char>,
+    csv_booleans: CsvBooleans,
+) -> Result<Vec<Vec<CellValue>>, AppError> {
+    let path = std::path::Path::new(&path);
+    super::reader::read_csv_rows(path, start_row, row_count, delimiter, csv_booleans)
+}
+
+/// 只读取 xlsx 中定义名称（或表格）对应的区域（不修改编辑器状态）
+pub fn do_read_named_range(path: String, name: String) -> Result<SheetData, AppError> {
+    super::reader::read_named_range(std::path::Path::new(&path), &name)
+}
+
+/// 取消正在进行的读取
+pub fn do_cancel_read() {
+    read_cancel_token().store(true, Ordering::Relaxed);
 }
 
 /// 初始化编辑器状态（用于新建文件）
@@ -20,37 +317,176 @@ pub fn do_init_file(file_data: FileData) -> Result<(), AppError> {
     Ok(())
 }
 
+/// 新建空白工作簿：一个 rows×cols 的空 sheet，替换当前编辑器状态（清空撤销历史）
+pub fn do_new_file(rows: usize, cols: usize, sheet_name: Option<String>) -> Result<FileData, AppError> {
+    let (rows, cols) = check_sheet_size((rows, cols))?;
+    let name = match sheet_name {
+        Some(name) => validate_sheet_name([], &name)?,
+        None => "Sheet1".to_string(),
+    };
+
+    let sheet = SheetData {
+        name,
+        rows: vec![vec![CellValue::Null; cols]; rows],
+        merges: vec![],
+        visible: true,
+        has_header: false,
+        row_heights: Vec::new(),
+        freeze: None,
+        index: Arc::default(),
+    };
+
+    let file_data = FileData {
+        file_name: NEW_FILE_NAME.to_string(),
+        sheets: vec![sheet],
+        delimiter: None,
+        warnings: Vec::new(),
+    };
+    init_editor_state(file_data.clone());
+    Ok(file_data)
+}
+
 fn init_editor_state(file_data: FileData) {
     let state = crate::commands::get_state();
-    {
-        let mut state_guard = state.write().unwrap();
-        *state_guard = Some(EditorState::new(file_data.clone()));
+    let mut state_guard = state.write().unwrap();
+    let mut editor_state = EditorState::new(file_data);
+    // 重新加载文件时保留用户偏好设置
+    if let Some(previous) = state_guard.as_ref() {
+        editor_state.sync_index = previous.sync_index;
+        editor_state.default_sheet_size = previous.default_sheet_size;
+        editor_state.default_cell = previous.default_cell.clone();
+        editor_state.tokenizer = previous.tokenizer;
+    }
+    // 索引默认在首次搜索时构建；开启 sync_index 时立即构建，保证加载后即可确定地搜索
+    if editor_state.sync_index {
+        editor_state.rebuild_all_indices();
+    }
+    *state_guard = Some(editor_state);
+}
+
+/// 保存文件，并以 "save-complete" / "save-error" 事件通知结果
+/// 只有写入成功后才更新编辑器状态中的文件数据和内容哈希（is_dirty 据此判断）
+pub fn do_save_file(
+    app: &AppHandle,
+    path: String,
+    file_data: FileData,
+    options: WriteOptions,
+) -> Result<SaveResult, AppError> {
+    let result = save_and_store(app, path, file_data, options);
+    match &result {
+        Ok(saved) => {
+            let _ = app.emit(SAVE_COMPLETE_EVENT, saved);
+        }
+        Err(e) => {
+            let _ = app.emit(SAVE_ERROR_EVENT, e);
+        }
+    }
+    result
+}
+
+/// 冻结窗格和表头标记只通过命令修改后端状态，前端发来的数据中可能仍是旧值，保存前以后端为准
+/// （按位置对应，名称不同的 sheet 保留前端的值）
+fn apply_sheet_settings(file_data: &mut FileData, current: &FileData) {
+    for (sheet, current) in file_data.sheets.iter_mut().zip(&current.sheets) {
+        if sheet.name == current.name {
+            sheet.freeze = current.freeze;
+            sheet.has_header = current.has_header;
+        }
     }
-    // 异步构建索引（后台线程）
-    spawn_rebuild_all_sheets_index(state.clone());
 }
 
-/// 保存文件
-pub fn do_save_file(path: String, file_data: FileData) -> Result<(), AppError> {
+/// 写入文件，成功后用已保存的数据替换编辑器状态中的文件数据
+fn save_and_store(
+    app: &AppHandle,
+    path: String,
+    mut file_data: FileData,
+    options: WriteOptions,
+) -> Result<SaveResult, AppError> {
+    let state = crate::commands::get_state();
+    if let Some(editor_state) = state.read().unwrap().as_ref() {
+        apply_sheet_settings(&mut file_data, &editor_state.file_data);
+    }
+
     let path = std::path::Path::new(&path);
-    super::writer::save_file(path, &file_data)?;
+    let result = super::writer::save_file(path, &file_data, &options, &|percent| {
+        let _ = app.emit(WRITE_PROGRESS_EVENT, percent);
+    })?;
 
     // 更新编辑器状态中的文件数据
-    let state = crate::commands::get_state();
     let mut state_guard = state.write().unwrap();
     if let Some(editor_state) = state_guard.as_mut() {
         editor_state.file_data = file_data;
+        editor_state.saved_hash = editor_state.file_data.content_hash();
     }
 
-    Ok(())
+    Ok(result)
+}
+
+/// 默认保存目录：源文件所在目录，其次是用户的文档目录、主目录，最后是当前工作目录
+fn default_save_dir(source_path: Option<&str>) -> PathBuf {
+    let dir = source_path
+        .and_then(|p| Path::new(p).parent())
+        .filter(|d| !d.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .or_else(dirs::document_dir)
+        .or_else(dirs::home_dir)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    std::path::absolute(&dir).unwrap_or(dir)
+}
+
+/// 默认保存文件名模板
+pub const DEFAULT_SAVE_NAME_TEMPLATE: &str = "{name}_edited.{ext}";
+
+/// 按模板生成保存文件名：{name} 为去掉扩展名的原文件名，{ext} 为保存格式的扩展名（xlsx），
+/// {date} 为本地日期（YYYY-MM-DD）
+fn render_save_name(template: &str, file_name: &str) -> String {
+    let name = match file_name.rfind('.') {
+        Some(dot_pos) => &file_name[..dot_pos],
+        None => file_name,
+    };
+    template
+        .replace("{name}", name)
+        .replace("{ext}", "xlsx")
+        .replace("{date}", &chrono::Local::now().format("%Y-%m-%d").to_string())
 }
 
-/// 获取默认保存路径
-pub fn do_get_default_save_path(file_name: String) -> String {
-    if let Some(dot_pos) = file_name.rfind('.') {
-        let name = &file_name[..dot_pos];
-        format!("{}_edited.xlsx", name)
-    } else {
-        format!("{}_edited.xlsx", file_name)
+/// 获取默认保存路径（绝对路径）：{目录}/{按模板生成的文件名}，模板默认为 "{name}_edited.{ext}"
+pub fn do_get_default_save_path(file_name: String, source_path: Option<String>, template: Option<String>) -> String {
+    let template = template.filter(|t| !t.trim().is_empty());
+    let save_name = render_save_name(template.as_deref().unwrap_or(DEFAULT_SAVE_NAME_TEMPLATE), &file_name);
+    default_save_dir(source_path.as_deref())
+        .join(save_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test_util::{file_data, text};
+    use crate::types::FreezePanes;
+
+    #[test]
+    fn save_keeps_freeze_and_header_set_in_the_backend() {
+        let mut current = file_data(vec![vec![vec![text("a")]], vec![vec![text("b")]]]);
+        current.sheets[0].freeze = Some(FreezePanes { rows: 1, cols: 2 });
+        current.sheets[0].has_header = true;
+        current.sheets[1].name = "Renamed".to_string();
+        current.sheets[1].has_header = true;
+
+        let mut incoming = file_data(vec![vec![vec![text("a")]], vec![vec![text("b")]]]);
+        incoming.sheets[1].freeze = Some(FreezePanes { rows: 3, cols: 0 });
+        apply_sheet_settings(&mut incoming, &current);
+
+        assert_eq!(incoming.sheets[0].freeze, Some(FreezePanes { rows: 1, cols: 2 }));
+        assert!(incoming.sheets[0].has_header);
+        assert_eq!(incoming.sheets[1].freeze, Some(FreezePanes { rows: 3, cols: 0 }));
+        assert!(!incoming.sheets[1].has_header);
+
+        // 清除冻结同样以后端为准
+        current.sheets[0].freeze = None;
+        apply_sheet_settings(&mut incoming, &current);
+        assert_eq!(incoming.sheets[0].freeze, None);
     }
 }
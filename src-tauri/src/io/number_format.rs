@@ -0,0 +1,388 @@
+use crate::types::CellValue;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader as XmlReader;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek};
+use zip::ZipArchive;
+
+/// Number formats of an xlsx workbook, used to give numeric cells the display
+/// text Excel shows for them (currency, percentages, thousands separators).
+///
+/// calamine does not expose format codes, so they are read from the package
+/// directly: styles.xml maps each cell style to a format code, and each
+/// worksheet maps its cells to styles.
+pub struct NumberFormats {
+    /// Format code of each cell style (cellXfs index); None for General
+    styles: Vec<Option<String>>,
+    /// Styled cells of each sheet, by sheet name: (row, col, style index)
+    cells: HashMap<String, Vec<(usize, usize, usize)>>,
+}
+
+impl NumberFormats {
+    /// Read the number formats of an xlsx package. Display text is cosmetic,
+    /// so anything missing or malformed yields None instead of an error.
+    pub fn read<RS: Read + Seek>(input: RS) -> Option<Self> {
+        let mut archive = ZipArchive::new(input).ok()?;
+        let styles = read_styles(&mut archive)?;
+        let mut cells = HashMap::new();
+        // Only General in use: nothing to display differently
+        if styles.iter().all(Option::is_none) {
+            return Some(NumberFormats { styles, cells });
+        }
+
+        let targets = read_relationships(&mut archive)?;
+        for (name, id) in read_sheet_ids(&mut archive)? {
+            let Some(target) = targets.get(&id) else { continue };
+            let path = match target.strip_prefix('/') {
+                Some(absolute) => absolute.to_string(),
+                None => format!("xl/{}", target),
+            };
+            if let Some(styled) = read_styled_cells(&mut archive, &path, &styles) {
+                cells.insert(name, styled);
+            }
+        }
+        Some(NumberFormats { styles, cells })
+    }
+
+    /// Wrap the numeric cells of `sheet_name` whose format changes how they
+    /// look as `CellValue::Formatted`. Formula cells get their cached result
+    /// wrapped. Rows are anchored at A1, like the positions in the sheet XML.
+    pub fn apply(&self, sheet_name: &str, rows: &mut [Vec<CellValue>]) {
+        let Some(cells) = self.cells.get(sheet_name) else { return };
+        for &(row, col, style) in cells {
+            let Some(Some(code)) = self.styles.get(style) else { continue };
+            let Some(mut cell) = rows.get_mut(row).and_then(|r| r.get_mut(col)) else { continue };
+            while let CellValue::Formula { cached, .. } = cell {
+                cell = cached;
+            }
+            let Some(n) = cell.as_f64() else { continue };
+            let Some(display) = format_with_code(n, code) else { continue };
+            if display != cell.to_text() {
+                let value = std::mem::replace(cell, CellValue::Null);
+                *cell = CellValue::Formatted { value: Box::new(value), display };
+            }
+        }
+    }
+}
+
+/// Open a package part as an XML reader
+fn open_part<'a, RS: Read + Seek>(
+    archive: &'a mut ZipArchive<RS>,
+    path: &str,
+) -> Option<XmlReader<BufReader<zip::read::ZipFile<'a, RS>>>> {
+    let file = archive.by_name(path).ok()?;
+    Some(XmlReader::from_reader(BufReader::new(file)))
+}
+
+/// Unescaped value of the attribute with local name `key`
+fn attribute(element: &BytesStart, key: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == key)
+        .and_then(|attr| attr.decode_and_unescape_value(element.decoder()).ok())
+        .map(|value| value.into_owned())
+}
+
+/// Call `visit` with every start or empty element of the part, and with
+/// `None` for every end element
+fn for_each_element<R: BufRead>(
+    reader: &mut XmlReader<R>,
+    mut visit: impl FnMut(Option<&BytesStart>, &[u8]),
+) -> Option<()> {
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf).ok()? {
+            Event::Start(e) => visit(Some(&e), e.local_name().as_ref()),
+            Event::Empty(e) => {
+                visit(Some(&e), e.local_name().as_ref());
+                visit(None, e.local_name().as_ref());
+            }
+            Event::End(e) => visit(None, e.local_name().as_ref()),
+            Event::Eof => return Some(()),
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// Format code of every cell style in xl/styles.xml, resolving built-in
+/// format ids. General and unknown built-in ids are None.
+fn read_styles<RS: Read + Seek>(archive: &mut ZipArchive<RS>) -> Option<Vec<Option<String>>> {
+    let mut reader = open_part(archive, "xl/styles.xml")?;
+    let mut custom: HashMap<u32, String> = HashMap::new();
+    let mut style_ids: Vec<u32> = Vec::new();
+    let mut in_cell_xfs = false;
+
+    for_each_element(&mut reader, |element, name| match (element, name) {
+        (Some(e), b"numFmt") => {
+            let id = attribute(e, b"numFmtId").and_then(|id| id.parse().ok());
+            if let (Some(id), Some(code)) = (id, attribute(e, b"formatCode")) {
+                custom.insert(id, code);
+            }
+        }
+        (Some(_), b"cellXfs") => in_cell_xfs = true,
+        (None, b"cellXfs") => in_cell_xfs = false,
+        // cellStyleXfs holds xf elements too; only cellXfs is indexed by cells
+        (Some(e), b"xf") if in_cell_xfs => {
+            style_ids.push(attribute(e, b"numFmtId").and_then(|id| id.parse().ok()).unwrap_or(0));
+        }
+        _ => {}
+    })?;
+
+    Some(
+        style_ids
+            .into_iter()
+            .map(|id| {
+                custom
+                    .get(&id)
+                    .cloned()
+                    .or_else(|| builtin_format(id).map(str::to_string))
+                    .filter(|code| !code.eq_ignore_ascii_case("General"))
+            })
+            .collect(),
+    )
+}
+
+/// Built-in number formats with a locale-independent code. Ids missing here
+/// are General, scientific, dates/times, or locale-dependent currency formats.
+fn builtin_format(id: u32) -> Option<&'static str> {
+    match id {
+        1 => Some("0"),
+        2 => Some("0.00"),
+        3 => Some("#,##0"),
+        4 => Some("#,##0.00"),
+        9 => Some("0%"),
+        10 => Some("0.00%"),
+        37 => Some("#,##0 ;(#,##0)"),
+        38 => Some("#,##0 ;[Red](#,##0)"),
+        39 => Some("#,##0.00;(#,##0.00)"),
+        40 => Some("#,##0.00;[Red](#,##0.00)"),
+        _ => None,
+    }
+}
+
+/// Relationship targets of xl/workbook.xml by relationship id
+fn read_relationships<RS: Read + Seek>(archive: &mut ZipArchive<RS>) -> Option<HashMap<String, String>> {
+    let mut reader = open_part(archive, "xl/_rels/workbook.xml.rels")?;
+    let mut targets = HashMap::new();
+    for_each_element(&mut reader, |element, name| {
+        if let (Some(e), b"Relationship") = (element, name) {
+            if let (Some(id), Some(target)) = (attribute(e, b"Id"), attribute(e, b"Target")) {
+                targets.insert(id, target);
+            }
+        }
+    })?;
+    Some(targets)
+}
+
+/// Sheet names with their relationship ids, from xl/workbook.xml
+fn read_sheet_ids<RS: Read + Seek>(archive: &mut ZipArchive<RS>) -> Option<Vec<(String, String)>> {
+    let mut reader = open_part(archive, "xl/workbook.xml")?;
+    let mut sheets = Vec::new();
+    for_each_element(&mut reader, |element, name| {
+        if let (Some(e), b"sheet") = (element, name) {
+            if let (Some(name), Some(id)) = (attribute(e, b"name"), attribute(e, b"id")) {
+                sheets.push((name, id));
+            }
+        }
+    })?;
+    Some(sheets)
+}
+
+/// Cells of a worksheet part whose style has a format code
+fn read_styled_cells<RS: Read + Seek>(
+    archive: &mut ZipArchive<RS>,
+    path: &str,
+    styles: &[Option<String>],
+) -> Option<Vec<(usize, usize, usize)>> {
+    let mut reader = open_part(archive, path)?;
+    let mut cells = Vec::new();
+    for_each_element(&mut reader, |element, name| {
+        let (Some(e), b"c") = (element, name) else { return };
+        let style = attribute(e, b"s").and_then(|s| s.parse::<usize>().ok());
+        let Some(style) = style.filter(|&s| matches!(styles.get(s), Some(Some(_)))) else { return };
+        if let Some((row, col)) = attribute(e, b"r").as_deref().and_then(parse_cell_ref) {
+            cells.push((row, col, style));
+        }
+    })?;
+    Some(cells)
+}
+
+/// Zero-based (row, col) of an A1-style reference such as "B3"
+fn parse_cell_ref(reference: &str) -> Option<(usize, usize)> {
+    let split = reference.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    if letters.is_empty() || !letters.bytes().all(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    let col = letters.bytes().fold(0usize, |acc, b| acc * 26 + (b - b'A' + 1) as usize) - 1;
+    let row = digits.parse::<usize>().ok()?.checked_sub(1)?;
+    Some((row, col))
+}
+
+/// Split a format code into its `;`-separated sections
+fn split_sections(code: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let (mut start, mut in_quotes, mut in_brackets, mut escaped) = (0, false, false, false);
+    for (i, c) in code.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if !in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => in_brackets = true,
+            ']' if !in_quotes => in_brackets = false,
+            ';' if !in_quotes && !in_brackets => {
+                sections.push(&code[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    sections.push(&code[start..]);
+    sections
+}
+
+/// Part of a format section
+enum Token {
+    Literal(String),
+    /// Digit placeholder: '0', '#' or '?'
+    Digit(char),
+    Decimal,
+    Comma,
+}
+
+/// Tokenize one format section, or None for features not handled here:
+/// dates and times, scientific notation, text sections and conditions
+fn tokenize(section: &str) -> Option<(Vec<Token>, usize)> {
+    let mut tokens = Vec::new();
+    let mut percents = 0;
+    let mut chars = section.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => tokens.push(Token::Literal(chars.by_ref().take_while(|&c| c != '"').collect())),
+            '\\' => tokens.push(Token::Literal(chars.next()?.to_string())),
+            // `_x` pads with the width of x; `*x` repeats x to fill the cell
+            '_' => {
+                chars.next()?;
+                tokens.push(Token::Literal(" ".to_string()));
+            }
+            '*' => {
+                chars.next()?;
+            }
+            '[' => {
+                let content: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                if let Some(currency) = content.strip_prefix('$') {
+                    // [$€-407]: currency symbol followed by a locale id
+                    let symbol = currency.split('-').next().unwrap_or_default();
+                    tokens.push(Token::Literal(symbol.to_string()));
+                } else if !content.starts_with(|c: char| c.is_ascii_uppercase())
+                    || !content.chars().all(|c| c.is_ascii_alphanumeric())
+                {
+                    // Conditions ([>100]) and elapsed times ([h]); colors ([Red]) are ignored
+                    return None;
+                }
+            }
+            '0' | '#' | '?' => tokens.push(Token::Digit(c)),
+            '.' => tokens.push(Token::Decimal),
+            ',' => tokens.push(Token::Comma),
+            '%' => {
+                percents += 1;
+                tokens.push(Token::Literal("%".to_string()));
+            }
+            '@' | 'E' | 'e' => return None,
+            c if c.is_alphabetic() => return None,
+            c => tokens.push(Token::Literal(c.to_string())),
+        }
+    }
+    Some((tokens, percents))
+}
+
+/// Concatenated literal text of `tokens`
+fn literals(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Literal(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Insert a comma between every group of three digits
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Format `n` with an Excel number format code, or None when the code uses
+/// something not handled here (dates and times, scientific notation, text
+/// sections, conditions, literals between digits). Negative numbers use the
+/// second section when there is one, zero the third.
+pub fn format_with_code(n: f64, code: &str) -> Option<String> {
+    if !n.is_finite() {
+        return None;
+    }
+    let sections = split_sections(code);
+    let (section, value, sign) = if n == 0.0 && sections.len() >= 3 {
+        (sections[2], 0.0, "")
+    } else if n < 0.0 && sections.len() >= 2 {
+        (sections[1], -n, "")
+    } else if n < 0.0 {
+        (sections[0], -n, "-")
+    } else {
+        (sections[0], n, "")
+    };
+    let (tokens, percents) = tokenize(section)?;
+
+    let is_number = |t: &Token| matches!(t, Token::Digit(_) | Token::Decimal | Token::Comma);
+    let Some(first) = tokens.iter().position(is_number) else {
+        // A section without placeholders is shown as-is
+        return Some(literals(&tokens));
+    };
+    let last = tokens.iter().rposition(is_number)?;
+    if !tokens[first..=last].iter().all(is_number) {
+        return None;
+    }
+    let number = &tokens[first..=last];
+    let decimal = number.iter().position(|t| matches!(t, Token::Decimal));
+    let (integer, fraction) = number.split_at(decimal.unwrap_or(number.len()));
+
+    // Commas between integer placeholders group thousands; trailing ones scale by 1000
+    let last_digit = integer.iter().rposition(|t| matches!(t, Token::Digit(_)));
+    let scale_commas = last_digit.map_or(integer.len(), |d| integer.len() - d - 1);
+    let grouped = integer[..integer.len() - scale_commas].iter().any(|t| matches!(t, Token::Comma));
+    let min_integer = integer.iter().filter(|t| matches!(t, Token::Digit('0'))).count();
+    let min_fraction = fraction.iter().filter(|t| matches!(t, Token::Digit('0'))).count();
+    let max_fraction = fraction.iter().filter(|t| matches!(t, Token::Digit(_))).count();
+
+    let value = value * 100f64.powi(percents as i32) / 1000f64.powi(scale_commas as i32);
+    let rounded = format!("{:.*}", max_fraction, value);
+    let (int_part, frac_part) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let frac_part = frac_part.trim_end_matches('0');
+    let frac_part = format!("{:0<width$}", frac_part, width = min_fraction);
+    let int_part = match int_part.trim_start_matches('0') {
+        "" => "0".repeat(min_integer),
+        digits => format!("{:0>width$}", digits, width = min_integer),
+    };
+    let int_part = if grouped { group_thousands(&int_part) } else { int_part };
+
+    let mut text = format!("{}{}{}", sign, literals(&tokens[..first]), int_part);
+    if decimal.is_some() {
+        text.push('.');
+        text.push_str(&frac_part);
+    }
+    text.push_str(&literals(&tokens[last + 1..]));
+    // A rounded-away negative shows no sign, as in Excel
+    if sign == "-" && !text.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+        text.remove(0);
+    }
+    // `_)`-style padding only aligns columns in Excel
+    Some(text.trim().to_string())
+}
@@ -0,0 +1,42 @@
+use std::cell::Cell;
+
+/// Progress reporter for long-running reads/writes.
+///
+/// Converts done/total counts into a 0-100 percentage and only invokes the
+/// callback when the percentage actually changes, so callers can report on
+/// every row without flooding the frontend with events.
+pub struct Progress<'a> {
+    callback: &'a dyn Fn(u8),
+    last: Cell<Option<u8>>,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(callback: &'a dyn Fn(u8)) -> Self {
+        Self {
+            callback,
+            last: Cell::new(None),
+        }
+    }
+
+    /// Report progress as `done` out of `total` units
+    pub fn report(&self, done: usize, total: usize) {
+        let percent = (done.min(total) * 100).checked_div(total).unwrap_or(100) as u8;
+
+        if self.last.get() != Some(percent) {
+            self.last.set(Some(percent));
+            (self.callback)(percent);
+        }
+    }
+
+    /// Report progress within one sheet of a multi-sheet workbook.
+    /// Each sheet is weighted equally since row counts are only known per sheet.
+    pub fn report_sheet(&self, sheet: usize, sheet_count: usize, row: usize, row_count: usize) {
+        let sheet_fraction = (row.min(row_count) * 100).checked_div(row_count).unwrap_or(100);
+        self.report(sheet * 100 + sheet_fraction, sheet_count * 100);
+    }
+
+    /// Report completion
+    pub fn finish(&self) {
+        self.report(1, 1);
+    }
+}
@@ -1,19 +1,29 @@
-use calamine::{open_workbook, Reader, Xlsx, Xls, Ods, Data};
+use calamine::{Reader, Xlsx, Xls, Ods, Data, Range, SheetVisible};
 
+use super::number_format::NumberFormats;
+use super::progress::Progress;
 use crate::error::AppError;
-use crate::types::{CellValue, FileData, MergeRange, SheetData, SheetIndex};
-use csv::ReaderBuilder;
+use crate::ops::a1::parse_a1_range;
+use crate::types::{
+    CellPosition, CellValue, CsvBooleans, FileData, FileFormat, MergeRange, ReadOptions, SheetData,
+};
+use csv::{ReaderBuilder, StringRecord};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 
 
+/// Convert a calamine cell. Text is kept verbatim, even when it looks like a
+/// number ("007", or a long ID stored as text), so its type is never guessed.
 fn cell_to_value(cell: Data) -> CellValue {
     match cell {
         Data::String(s) => CellValue::String(s),
-        Data::Float(f) => CellValue::Number(f),
-        Data::Int(i) => CellValue::Number(i as f64),
+        Data::Float(f) => CellValue::number(f),
+        Data::Int(i) => CellValue::Integer(i),
         Data::Bool(b) => CellValue::Boolean(b),
-        Data::DateTime(dt) => CellValue::Number(dt.as_f64()),
+        Data::DateTime(dt) => CellValue::number(dt.as_f64()),
         Data::DateTimeIso(s) => CellValue::String(s),
         Data::DurationIso(s) => CellValue::String(s),
         Data::Error(e) => CellValue::String(format!("{:?}", e)),
@@ -21,39 +31,98 @@ fn cell_to_value(cell: Data) -> CellValue {
     }
 }
 
-fn read_excel(path: &Path) -> Result<FileData, AppError> {
-    let extension = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .ok_or(AppError::UnsupportedFormat)?;
+/// Bail out with a "cancelled" error once the cancellation flag is set
+fn check_cancelled(cancel: &AtomicBool) -> Result<(), AppError> {
+    if cancel.load(Ordering::Relaxed) {
+        Err(AppError::ReadError("cancelled".to_string()))
+    } else {
+        Ok(())
+    }
+}
 
-    let file_name = path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+/// Convert a worksheet range into rows, reporting progress as rows are converted.
+/// The used range may not start at A1; it is padded with leading empty rows and
+/// columns so every cell keeps its worksheet position (merges and formulas
+/// refer to absolute positions).
+fn range_to_rows(
+    range: &Range<Data>,
+    progress: &Progress,
+    cancel: &AtomicBool,
+    sheet: usize,
+    sheet_count: usize,
+) -> Result<Vec<Vec<CellValue>>, AppError> {
+    let (origin_row, origin_col) = range.start().map_or((0, 0), |(r, c)| (r as usize, c as usize));
+    let row_count = range.height();
+    let width = origin_col + range.width();
+
+    let mut rows = vec![vec![CellValue::Null; width]; origin_row];
+    rows.reserve(row_count);
+    for (row_idx, row) in range.rows().enumerate() {
+        check_cancelled(cancel)?;
+        progress.report_sheet(sheet, sheet_count, row_idx + 1, row_count);
+        let mut values = Vec::with_capacity(width);
+        values.resize(origin_col, CellValue::Null);
+        values.extend(row.iter().map(|cell| cell_to_value(cell.clone())));
+        rows.push(values);
+    }
+    Ok(rows)
+}
 
-    let sheets: Vec<SheetData> = match extension.as_str() {
-        "xlsx" => read_xlsx(path)?,
-        "xls" => read_xls(path)?,
-        "ods" => read_ods(path)?,
-        _ => return Err(AppError::UnsupportedFormat),
+/// Wrap formula cells as `CellValue::Formula`, keeping the cached value calamine
+/// read for them. Rows are anchored at A1 (see `range_to_rows`), so formula
+/// positions are used as absolute positions.
+fn apply_formulas(rows: &mut [Vec<CellValue>], formulas: &Range<String>) {
+    let Some((formula_row, formula_col)) = formulas.start() else {
+        return;
     };
 
-    Ok(FileData { file_name, sheets })
+    for (r, c, expr) in formulas.used_cells() {
+        if expr.is_empty() {
+            continue;
+        }
+        let row = formula_row as usize + r;
+        let col = formula_col as usize + c;
+        if let Some(cell) = rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            let cached = std::mem::replace(cell, CellValue::Null);
+            *cell = CellValue::Formula {
+                expr: format!("={}", expr),
+                cached: Box::new(cached),
+            };
+        }
+    }
+}
+
+/// Sheet names paired with their visibility, in workbook order
+fn sheet_list<RS: Read + Seek, W: Reader<RS>>(workbook: &W) -> Vec<(String, bool)> {
+    workbook
+        .sheets_metadata()
+        .iter()
+        .map(|sheet| (sheet.name.clone(), sheet.visible == SheetVisible::Visible))
+        .collect()
 }
 
-fn read_xlsx(path: &Path) -> Result<Vec<SheetData>, AppError> {
-    let mut workbook: Xlsx<std::io::BufReader<std::fs::File>> =
-        open_workbook(path).map_err(|e: calamine::XlsxError| AppError::ReadError(e.to_string()))?;
+/// Sheets that fail to load are skipped and reported in `warnings`, so the
+/// remaining sheets keep their original order
+fn read_xlsx<RS: Read + Seek>(
+    mut reader: RS,
+    warnings: &mut Vec<String>,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<Vec<SheetData>, AppError> {
+    // calamine does not expose number formats, so read them from the package
+    // first and rewind for calamine
+    let number_formats = NumberFormats::read(&mut reader);
+    reader
+        .seek(SeekFrom::Start(0))
+        .map_err(|e| AppError::ReadError(e.to_string()))?;
+    let mut workbook = Xlsx::new(reader).map_err(|e| AppError::ReadError(e.to_string()))?;
 
     // Load merged regions first
     workbook
         .load_merged_regions()
         .map_err(|e| AppError::ReadError(e.to_string()))?;
 
-    let sheet_names = workbook.sheet_names().to_vec();
+    let sheet_list = sheet_list(&workbook);
 
     // Collect merged regions data to avoid borrowing issues
     let merged_data: Vec<(String, u32, u16, u32, u16)> = workbook
@@ -72,20 +141,22 @@ fn read_xlsx(path: &Path) -> Result<Vec<SheetData>, AppError> {
 
     let mut sheets: Vec<SheetData> = Vec::new();
 
-    for sheet_name in &sheet_names {
+    for (sheet_idx, (sheet_name, visible)) in sheet_list.iter().enumerate() {
         let range = match workbook.worksheet_range(sheet_name) {
             Ok(r) => r,
-            Err(_) => continue,
+            Err(e) => {
+                warnings.push(format!("Sheet '{}' could not be read: {}", sheet_name, e));
+                continue;
+            }
         };
 
-        let rows: Vec<Vec<CellValue>> = range
-            .rows()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| cell_to_value(cell.clone()))
-                    .collect()
-            })
-            .collect();
+        let mut rows = range_to_rows(&range, progress, cancel, sheet_idx, sheet_list.len())?;
+        if let Ok(formulas) = workbook.worksheet_formula(sheet_name) {
+            apply_formulas(&mut rows, &formulas);
+        }
+        if let Some(number_formats) = &number_formats {
+            number_formats.apply(sheet_name, &mut rows);
+        }
 
         // Read merged cells for this sheet
         let merges: Vec<MergeRange> = merged_data
@@ -99,11 +170,82 @@ fn read_xlsx(path: &Path) -> Result<Vec<SheetData>, AppError> {
             })
             .collect();
 
-        let index = SheetIndex::default();
+        let index = Arc::default();
         sheets.push(SheetData {
             name: sheet_name.clone(),
             rows,
             merges,
+            visible: *visible,
+            has_header: false,
+            row_heights: Vec::new(),
+            freeze: None,
+            index,
+        });
+    }
+
+    Ok(sheets)
+}
+
+fn read_xls<RS: Read + Seek>(
+    reader: RS,
+    warnings: &mut Vec<String>,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<Vec<SheetData>, AppError> {
+    let mut workbook = Xls::new(reader).map_err(|e| AppError::ReadError(e.to_string()))?;
+    read_unmerged_sheets(&mut workbook, warnings, progress, cancel)
+}
+
+fn read_ods<RS: Read + Seek>(
+    reader: RS,
+    warnings: &mut Vec<String>,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<Vec<SheetData>, AppError> {
+    let mut workbook = Ods::new(reader).map_err(|e| AppError::ReadError(e.to_string()))?;
+    read_unmerged_sheets(&mut workbook, warnings, progress, cancel)
+}
+
+/// Read every sheet of a workbook whose format has no merged-cell support
+/// in calamine (xls, ods). Sheets that fail to load are skipped and reported
+/// in `warnings`, so the remaining sheets keep their original order.
+fn read_unmerged_sheets<RS, W>(
+    workbook: &mut W,
+    warnings: &mut Vec<String>,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<Vec<SheetData>, AppError>
+where
+    RS: Read + Seek,
+    W: Reader<RS>,
+    W::Error: std::fmt::Display,
+{
+    let sheet_list = sheet_list(workbook);
+    let mut sheets: Vec<SheetData> = Vec::new();
+
+    for (sheet_idx, (sheet_name, visible)) in sheet_list.iter().enumerate() {
+        let range = match workbook.worksheet_range(sheet_name) {
+            Ok(r) => r,
+            Err(e) => {
+                warnings.push(format!("Sheet '{}' could not be read: {}", sheet_name, e));
+                continue;
+            }
+        };
+
+        let mut rows = range_to_rows(&range, progress, cancel, sheet_idx, sheet_list.len())?;
+        if let Ok(formulas) = workbook.worksheet_formula(sheet_name) {
+            apply_formulas(&mut rows, &formulas);
+        }
+
+        let index = Arc::default();
+        sheets.push(SheetData {
+            name: sheet_name.clone(),
+            rows,
+            merges: Vec::new(),
+            visible: *visible,
+            has_header: false,
+            row_heights: Vec::new(),
+            freeze: None,
             index,
         });
     }
@@ -111,125 +253,658 @@ fn read_xlsx(path: &Path) -> Result<Vec<SheetData>, AppError> {
     Ok(sheets)
 }
 
-fn read_xls(path: &Path) -> Result<Vec<SheetData>, AppError> {
-    let mut workbook: Xls<std::io::BufReader<std::fs::File>> =
-        open_workbook(path).map_err(|e: calamine::XlsError| AppError::ReadError(e.to_string()))?;
-    let sheet_names = workbook.sheet_names().to_vec();
-    Ok(sheet_names
+/// Row count is unknown up front, so progress is estimated from bytes consumed
+/// out of `total_bytes`. Returns the sheet along with the delimiter used.
+fn read_csv<R: Read>(
+    input: R,
+    options: &ReadOptions,
+    total_bytes: usize,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<(Vec<SheetData>, char), AppError> {
+    let (mut reader, delimiter) = csv_reader(input, options.delimiter, options.flexible)?;
+    let mut rows: Vec<Vec<CellValue>> = Vec::new();
+
+    // Reuse one record buffer and convert each record straight into the final
+    // rows, so only the parsed cells are kept in memory
+    let mut record = StringRecord::new();
+    while read_csv_record(&mut reader, &mut record)? {
+        check_cancelled(cancel)?;
+        if let Some(position) = record.position() {
+            progress.report(position.byte() as usize, total_bytes);
+        }
+        rows.push(record.iter().map(|field| parse_csv_field(field, options)).collect());
+    }
+
+    if options.flexible {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut rows {
+            row.resize(width, CellValue::Null);
+        }
+    }
+
+    let index = Arc::default();
+    let sheet = SheetData {
+        name: "Sheet1".to_string(),
+        rows,
+        merges: vec![],
+        visible: true,
+        has_header: false,
+        row_heights: Vec::new(),
+        freeze: None,
+        index,
+    };
+    Ok((vec![sheet], delimiter))
+}
+
+/// Read `row_count` rows starting at `start_row` from a CSV file without
+/// loading the rest of it, so very large files can be paged through.
+/// Reading stops as soon as the requested window has been filled.
+pub fn read_csv_rows(
+    path: &Path,
+    start_row: usize,
+    row_count: usize,
+    delimiter: Option<char>,
+    csv_booleans: CsvBooleans,
+) -> Result<Vec<Vec<CellValue>>, AppError> {
+    let file = std::fs::File::open(path).map_err(|e| AppError::ReadError(e.to_string()))?;
+    let (mut reader, _) = csv_reader(file, delimiter, false)?;
+    let mut rows = Vec::with_capacity(row_count.min(1024));
+    let mut record = StringRecord::new();
+    let mut row_idx = 0;
+    let options = ReadOptions { csv_booleans, ..ReadOptions::default() };
+
+    while rows.len() < row_count && read_csv_record(&mut reader, &mut record)? {
+        if row_idx >= start_row {
+            rows.push(record.iter().map(|field| parse_csv_field(field, &options)).collect());
+        }
+        row_idx += 1;
+    }
+
+    Ok(rows)
+}
+
+/// Split a defined name's reference such as `'My Sheet'!$A$1:$C$10` into
+/// the sheet name and the corners of the area. Names referring to formulas,
+/// constants or several areas are not supported.
+fn parse_defined_range(reference: &str) -> Option<(String, CellPosition, CellPosition)> {
+    let (sheet, area) = reference.trim_start_matches('=').rsplit_once('!')?;
+    let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(quoted) => quoted.replace("''", "'"),
+        None => sheet.to_string(),
+    };
+    let (start, end) = parse_a1_range(area)?;
+    Some((sheet, start, end))
+}
+
+/// Read only the rectangle that a defined name, or failing that an Excel
+/// table, refers to. The sheet is named after `name`; a table's column
+/// headers become its first row and mark it as having a header. Names are
+/// matched case-insensitively, as in Excel. Cached values are read; formulas,
+/// merges and number formats are not.
+pub fn read_named_range(path: &Path, name: &str) -> Result<SheetData, AppError> {
+    if FileFormat::from_path(path) != Some(FileFormat::Xlsx) {
+        return Err(AppError::UnsupportedFormat);
+    }
+    let file = std::fs::File::open(path).map_err(|e| AppError::ReadError(e.to_string()))?;
+    let mut workbook = Xlsx::new(BufReader::new(file)).map_err(|e| AppError::ReadError(e.to_string()))?;
+
+    let defined = workbook
+        .defined_names()
         .iter()
-        .filter_map(|sheet_name| {
-            let range = workbook.worksheet_range(sheet_name).ok()?;
-            let rows: Vec<Vec<CellValue>> = range
-                .rows()
-                .map(|row| {
-                    row.iter()
-                        .map(|cell| cell_to_value(cell.clone()))
-                        .collect()
-                })
-                .collect();
-
-            // Read merged cells (Xlsx only, other formats not supported)
-            let merges: Vec<MergeRange> = Vec::new();
-
-            let index = SheetIndex::default();
-            Some(SheetData {
-                name: sheet_name.clone(),
-                rows,
-                merges,
-                index,
+        .find(|(defined, _)| defined.eq_ignore_ascii_case(name))
+        .map(|(_, reference)| reference.clone());
+
+    let (rows, has_header) = match defined {
+        Some(reference) => {
+            let (sheet_name, start, end) = parse_defined_range(&reference).ok_or_else(|| {
+                AppError::InvalidReference(format!("'{}' does not refer to a single cell range: {}", name, reference))
+            })?;
+            let range = workbook
+                .worksheet_range(&sheet_name)
+                .map_err(|e| AppError::ReadError(e.to_string()))?;
+            // Cells outside the used range are read as empty, so the full rectangle is kept
+            let area = range.range((start.row as u32, start.col as u32), (end.row as u32, end.col as u32));
+            (range_rows(&area), false)
+        }
+        None => {
+            workbook.load_tables().map_err(|e| AppError::ReadError(e.to_string()))?;
+            let table = workbook
+                .table_by_name(name)
+                .map_err(|_| AppError::InvalidReference(format!("No defined name or table named '{}'", name)))?;
+            let mut rows = vec![table.columns().iter().map(|c| CellValue::String(c.clone())).collect()];
+            rows.extend(range_rows(table.data()));
+            (rows, true)
+        }
+    };
+
+    Ok(SheetData {
+        name: name.to_string(),
+        rows,
+        merges: Vec::new(),
+        visible: true,
+        has_header,
+        row_heights: Vec::new(),
+        freeze: None,
+        index: Arc::default(),
+    })
+}
+
+/// Convert a range into rows without anchoring it at A1
+fn range_rows(range: &Range<Data>) -> Vec<Vec<CellValue>> {
+    range
+        .rows()
+        .map(|row| row.iter().map(|cell| cell_to_value(cell.clone())).collect())
+        .collect()
+}
+
+/// Delimiters considered when sniffing
+const DELIMITER_CANDIDATES: [u8; 4] = [b',', b';', b'\t', b'|'];
+/// How much of the input is sampled when sniffing the delimiter
+const DELIMITER_SAMPLE_BYTES: u64 = 64 * 1024;
+/// How many lines of the sample are compared when sniffing the delimiter
+const DELIMITER_SAMPLE_LINES: usize = 10;
+
+/// CSV reader over the sniffed sample followed by the rest of the input
+type SniffedCsvReader<R> = csv::Reader<std::io::Chain<Cursor<Vec<u8>>, R>>;
+
+/// Build a CSV reader using `delimiter`, or one sniffed from the start of the
+/// input when `None`. The sampled bytes are replayed in front of the rest of
+/// the input, so nothing is lost. Quoted fields may contain delimiters,
+/// newlines and doubled quotes. Unless `flexible`, a record with a different
+/// field count from the first one is an error.
+fn csv_reader<R: Read>(
+    mut input: R,
+    delimiter: Option<char>,
+    flexible: bool,
+) -> Result<(SniffedCsvReader<R>, char), AppError> {
+    let mut sample = Vec::new();
+    let delimiter = match delimiter {
+        Some(c) if c.is_ascii() => c as u8,
+        Some(c) => {
+            return Err(AppError::ReadError(format!(
+                "Delimiter '{}' must be a single ASCII character",
+                c
+            )))
+        }
+        None => {
+            (&mut input)
+                .take(DELIMITER_SAMPLE_BYTES)
+                .read_to_end(&mut sample)
+                .map_err(|e| AppError::ReadError(e.to_string()))?;
+            sniff_delimiter(&sample)
+        }
+    };
+
+    let reader = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .flexible(flexible)
+        .from_reader(Cursor::new(sample).chain(input));
+    Ok((reader, delimiter as char))
+}
+
+/// Pick the delimiter that splits the first few lines most consistently.
+/// A candidate scores best when every sampled line has the same non-zero
+/// count of it (outside quotes); ties go to the higher count, then to the
+/// order of `DELIMITER_CANDIDATES`. Falls back to a comma.
+fn sniff_delimiter(sample: &[u8]) -> u8 {
+    // Split on newlines outside quotes, so a quoted multi-line field is
+    // counted as part of its record instead of as separate short lines
+    let mut records: Vec<&[u8]> = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, &b) in sample.iter().enumerate() {
+        match b {
+            b'"' => in_quotes = !in_quotes,
+            b'\n' if !in_quotes => {
+                records.push(&sample[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    records.push(&sample[start..]);
+
+    let mut lines: Vec<&[u8]> = records
+        .into_iter()
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .take(DELIMITER_SAMPLE_LINES + 1)
+        .collect();
+    // The last line of a truncated sample may be cut off mid-record
+    if lines.len() > 1 && sample.len() as u64 >= DELIMITER_SAMPLE_BYTES {
+        lines.pop();
+    }
+    lines.truncate(DELIMITER_SAMPLE_LINES);
+
+    let count_outside_quotes = |line: &[u8], delimiter: u8| {
+        let mut in_quotes = false;
+        line.iter()
+            .filter(|&&b| {
+                if b == b'"' {
+                    in_quotes = !in_quotes;
+                }
+                !in_quotes && b == delimiter
             })
+            .count()
+    };
+
+    DELIMITER_CANDIDATES
+        .iter()
+        .enumerate()
+        .filter_map(|(order, &delimiter)| {
+            let counts: Vec<usize> = lines.iter().map(|line| count_outside_quotes(line, delimiter)).collect();
+            let first = *counts.first()?;
+            let consistent = first > 0 && counts.iter().all(|&c| c == first);
+            let total: usize = counts.iter().sum();
+            (total > 0).then_some(((consistent, first, total, std::cmp::Reverse(order)), delimiter))
         })
-        .collect())
+        .max_by_key(|(score, _)| *score)
+        .map_or(b',', |(_, delimiter)| delimiter)
+}
+
+/// Read the next record into `record`, returning false at end of file
+fn read_csv_record<R: Read>(reader: &mut csv::Reader<R>, record: &mut StringRecord) -> Result<bool, AppError> {
+    reader
+        .read_record(record)
+        .map_err(|e| AppError::ReadError(e.to_string()))
+}
+
+/// Date formats tried when `ReadOptions::date_formats` is empty. Only
+/// unambiguous formats are included; day/month orders must be opted into.
+const DEFAULT_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"];
+
+/// Parse `field` as a date using the first matching format, normalized to YYYY-MM-DD
+fn parse_date(field: &str, formats: &[String]) -> Option<String> {
+    let parse = |format: &str| chrono::NaiveDate::parse_from_str(field, format).ok();
+    let date = if formats.is_empty() {
+        DEFAULT_DATE_FORMATS.iter().find_map(|f| parse(f))
+    } else {
+        formats.iter().find_map(|f| parse(f))
+    }?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Infer a cell value from a CSV field: empty -> Null, then (when
+/// `parse_dates` is on) date, then number, boolean, text.
+/// Integers are parsed as i64 first so long IDs keep every digit. Fields with a
+/// leading zero (e.g. "007", "0908") stay text, matching how the editor treats
+/// typed input, so codes and zip codes are not normalized to numbers.
+/// "true"/"false" are always booleans; "1"/"0" are booleans only under
+/// `CsvBooleans::OneZero`, mirroring how the writer renders them.
+fn parse_csv_field(field: &str, options: &ReadOptions) -> CellValue {
+    let has_leading_zero = field.len() > 1 && field.starts_with('0') && field.as_bytes()[1].is_ascii_digit();
+    if field.is_empty() {
+        CellValue::Null
+    } else if let Some(date) = options.parse_dates.then(|| parse_date(field, &options.date_formats)).flatten() {
+        CellValue::Date { date }
+    } else if options.csv_booleans == CsvBooleans::OneZero && (field == "1" || field == "0") {
+        CellValue::Boolean(field == "1")
+    } else if has_leading_zero {
+        CellValue::String(field.to_string())
+    } else if let Ok(int) = field.parse::<i64>() {
+        CellValue::Integer(int)
+    } else if let Ok(num) = field.parse::<f64>() {
+        CellValue::number(num)
+    } else if field.eq_ignore_ascii_case("true") {
+        CellValue::Boolean(true)
+    } else if field.eq_ignore_ascii_case("false") {
+        CellValue::Boolean(false)
+    } else {
+        CellValue::String(field.to_string())
+    }
+}
+
+/// Transpose a sheet so columns become rows, padding ragged rows with `Null` first
+fn transpose_sheet(sheet: &mut SheetData) {
+    // Rows become columns, so per-row heights no longer apply
+    sheet.row_heights.clear();
+    let width = sheet.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut transposed = vec![Vec::with_capacity(sheet.rows.len()); width];
+
+    for mut row in std::mem::take(&mut sheet.rows) {
+        row.resize(width, CellValue::Null);
+        for (col, cell) in row.into_iter().enumerate() {
+            transposed[col].push(cell);
+        }
+    }
+    sheet.rows = transposed;
+
+    for merge in &mut sheet.merges {
+        *merge = MergeRange {
+            start_row: merge.start_col as u32,
+            start_col: merge.start_row as u16,
+            end_row: merge.end_col as u32,
+            end_col: merge.end_row as u16,
+        };
+    }
+}
+
+/// Error for a format that is recognised but cannot be read.
+/// Numbers documents store their tables as Snappy-compressed protobuf (IWA)
+/// archives with no maintained Rust parser, so point the user at an export
+/// instead of reporting a bare "unsupported format".
+fn unreadable_format(format: FileFormat) -> AppError {
+    match format {
+        FileFormat::Numbers => AppError::ReadError(
+            "Apple Numbers files cannot be opened directly; in Numbers choose \
+             File > Export To > Excel (or CSV) and open the exported file"
+                .to_string(),
+        ),
+        _ => AppError::UnsupportedFormat,
+    }
+}
+
+/// Read every sheet of a workbook in `format` from any seekable source
+fn read_sheets<RS: Read + Seek>(
+    input: RS,
+    format: FileFormat,
+    file_name: String,
+    options: &ReadOptions,
+    total_bytes: usize,
+    progress: &Progress,
+    cancel: &AtomicBool,
+) -> Result<FileData, AppError> {
+    let mut file_data = FileData {
+        file_name,
+        sheets: Vec::new(),
+        delimiter: None,
+        warnings: Vec::new(),
+    };
+    file_data.sheets = match format {
+        // .xlsm shares the OOXML container with .xlsx; macros are simply ignored
+        FileFormat::Xlsx => read_xlsx(input, &mut file_data.warnings, progress, cancel)?,
+        FileFormat::Xls => read_xls(input, &mut file_data.warnings, progress, cancel)?,
+        FileFormat::Ods => read_ods(input, &mut file_data.warnings, progress, cancel)?,
+        FileFormat::Numbers => return Err(unreadable_format(format)),
+        FileFormat::Csv => {
+            let (sheets, delimiter) = read_csv(input, options, total_bytes, progress, cancel)?;
+            file_data.delimiter = Some(delimiter);
+            sheets
+        }
+    };
+    Ok(file_data)
+}
+
+/// Truncate string cells longer than `max_len` characters, ending them with an
+/// ellipsis so the result is exactly `max_len` characters. Returns how many
+/// cells were truncated.
+fn truncate_long_cells(sheet: &mut SheetData, max_len: usize) -> usize {
+    let mut truncated = 0;
+    for cell in sheet.rows.iter_mut().flatten() {
+        let CellValue::String(text) = cell else { continue };
+        // Byte length bounds the char count, so short cells skip the char scan
+        if text.len() <= max_len {
+            continue;
+        }
+        if let Some((end, _)) = text.char_indices().nth(max_len.saturating_sub(1)) {
+            if text[end..].chars().nth(1).is_some() {
+                text.truncate(end);
+                text.push('…');
+                truncated += 1;
+            }
+        }
+    }
+    truncated
+}
+
+/// Apply read options and report completion
+fn finish_read(mut file_data: FileData, options: &ReadOptions, progress: &Progress) -> FileData {
+    if let Some(max_len) = options.max_cell_len {
+        for sheet in &mut file_data.sheets {
+            let truncated = truncate_long_cells(sheet, max_len);
+            if truncated > 0 {
+                file_data.warnings.push(format!(
+                    "Sheet '{}': {} cell(s) longer than {} characters were truncated",
+                    sheet.name, truncated, max_len
+                ));
+            }
+        }
+    }
+
+    if options.transpose {
+        file_data.sheets.iter_mut().for_each(transpose_sheet);
+    }
+
+    progress.finish();
+    file_data
+}
+
+/// Read a file, reporting 0-100 progress through `on_progress`.
+/// Setting `cancel` aborts the read with `AppError::ReadError("cancelled")`.
+pub fn read_file(
+    path: &Path,
+    options: &ReadOptions,
+    on_progress: &dyn Fn(u8),
+    cancel: &AtomicBool,
+) -> Result<FileData, AppError> {
+    let progress = Progress::new(on_progress);
+    let format = FileFormat::resolve(options.format, path).ok_or(AppError::UnsupportedFormat)?;
+    // Checked before opening: a .numbers document may be a package directory
+    if !format.can_read() {
+        return Err(unreadable_format(format));
+    }
+
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let file = std::fs::File::open(path).map_err(|e| AppError::ReadError(e.to_string()))?;
+    let total_bytes = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let file_data = read_sheets(BufReader::new(file), format, file_name, options, total_bytes, &progress, cancel)?;
+
+    Ok(finish_read(file_data, options, &progress))
 }
 
-fn read_ods(path: &Path) -> Result<Vec<SheetData>, AppError> {
-    let mut workbook: Ods<std::io::BufReader<std::fs::File>> =
-        open_workbook(path).map_err(|e: calamine::OdsError| AppError::ReadError(e.to_string()))?;
-    let sheet_names = workbook.sheet_names().to_vec();
-    Ok(sheet_names
+/// Split a fixed-width line into trimmed fields of the given character widths.
+/// Short lines yield Null for the missing fields; text past the last width is ignored.
+fn split_fixed_width(line: &str, widths: &[usize], options: &ReadOptions) -> Vec<CellValue> {
+    let mut rest = line;
+    widths
         .iter()
-        .filter_map(|sheet_name| {
-            let range = workbook.worksheet_range(sheet_name).ok()?;
-            let rows: Vec<Vec<CellValue>> = range
-                .rows()
-                .map(|row| {
-                    row.iter()
-                        .map(|cell| cell_to_value(cell.clone()))
-                        .collect()
-                })
-                .collect();
-
-            // Read merged cells (Xlsx only, other formats not supported)
-            let merges: Vec<MergeRange> = Vec::new();
-
-            let index = SheetIndex::default();
-            Some(SheetData {
-                name: sheet_name.clone(),
-                rows,
-                merges,
-                index,
-            })
+        .map(|&width| {
+            let end = rest.char_indices().nth(width).map_or(rest.len(), |(i, _)| i);
+            let (field, tail) = rest.split_at(end);
+            rest = tail;
+            parse_csv_field(field.trim(), options)
         })
-        .collect())
+        .collect()
 }
 
-fn read_csv(path: &Path) -> Result<FileData, AppError> {
+/// Read a fixed-width text file (no delimiter) into a single sheet, slicing
+/// each line by `widths` characters and inferring types like CSV fields.
+pub fn read_fixed_width(
+    path: &Path,
+    widths: &[usize],
+    options: &ReadOptions,
+    on_progress: &dyn Fn(u8),
+    cancel: &AtomicBool,
+) -> Result<FileData, AppError> {
+    if widths.is_empty() || widths.contains(&0) {
+        return Err(AppError::ReadError("column widths must be non-empty and greater than 0".to_string()));
+    }
+
+    let progress = Progress::new(on_progress);
     let file_name = path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown")
         .to_string();
 
-    let mut reader = ReaderBuilder::new()
-        .has_headers(false)
-        .from_path(path)
-        .map_err(|e| AppError::ReadError(e.to_string()))?;
-
+    let file = std::fs::File::open(path).map_err(|e| AppError::ReadError(e.to_string()))?;
+    let total_bytes = file.metadata().map(|m| m.len() as usize).unwrap_or(0);
+    let mut reader = BufReader::new(file);
     let mut rows: Vec<Vec<CellValue>> = Vec::new();
+    let mut line = String::new();
+    let mut bytes_read = 0;
 
-    for result in reader.records() {
-        let record = result.map_err(|e| AppError::ReadError(e.to_string()))?;
-        let row: Vec<CellValue> = record
-            .iter()
-            .map(|field| {
-                if field.is_empty() {
-                    CellValue::Null
-                } else if let Ok(num) = field.parse::<f64>() {
-                    CellValue::Number(num)
-                } else if field.to_lowercase() == "true" {
-                    CellValue::Boolean(true)
-                } else if field.to_lowercase() == "false" {
-                    CellValue::Boolean(false)
-                } else {
-                    CellValue::String(field.to_string())
-                }
-            })
-            .collect();
-        rows.push(row);
+    loop {
+        line.clear();
+        let n = reader.read_line(&mut line).map_err(|e| AppError::ReadError(e.to_string()))?;
+        if n == 0 {
+            break;
+        }
+        check_cancelled(cancel)?;
+        bytes_read += n;
+        progress.report(bytes_read, total_bytes);
+        let text = line.trim_end_matches(['\r', '\n']);
+        rows.push(split_fixed_width(text, widths, options));
     }
 
-    let index = SheetIndex::default();
-    Ok(FileData {
+    let file_data = FileData {
         file_name,
         sheets: vec![SheetData {
             name: "Sheet1".to_string(),
             rows,
             merges: vec![],
-            index,
+            visible: true,
+            has_header: false,
+            row_heights: Vec::new(),
+            freeze: None,
+            index: Arc::default(),
         }],
-    })
+        delimiter: None,
+        warnings: Vec::new(),
+    };
+
+    Ok(finish_read(file_data, options, &progress))
+}
+
+/// Read a workbook held in memory (e.g. a file dropped into the window as a blob).
+/// `file_name` is only used as the resulting `FileData::file_name`.
+pub fn read_bytes(
+    bytes: &[u8],
+    format: FileFormat,
+    file_name: &str,
+    options: &ReadOptions,
+) -> Result<FileData, AppError> {
+    let progress = Progress::new(&|_| {});
+    let cancel = AtomicBool::new(false);
+    let file_data = read_sheets(
+        Cursor::new(bytes),
+        format,
+        file_name.to_string(),
+        options,
+        bytes.len(),
+        &progress,
+        &cancel,
+    )?;
+
+    Ok(finish_read(file_data, options, &progress))
 }
 
-pub fn read_file(path: &Path) -> Result<FileData, AppError> {
-    let extension = path
-        .extension()
-        .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .ok_or(AppError::UnsupportedFormat)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test_util::text;
+
+    fn read_csv_bytes(input: &str, options: &ReadOptions) -> Result<Vec<Vec<CellValue>>, AppError> {
+        let file_data = read_bytes(input.as_bytes(), FileFormat::Csv, "test.csv", options)?;
+        Ok(file_data.sheets.into_iter().next().unwrap().rows)
+    }
+
+    #[test]
+    fn csv_quoted_fields_keep_newlines_commas_and_quotes() {
+        let input = "name,note\r\n\"Smith, J\",\"line 1\r\nline 2\nline 3\"\r\nx,\"say \"\"hi\"\"\"\r\n";
+        let rows = read_csv_bytes(input, &ReadOptions::default()).unwrap();
+        assert_eq!(
+            rows,
+            [
+                [text("name"), text("note")],
+                [text("Smith, J"), text("line 1\r\nline 2\nline 3")],
+                [text("x"), text("say \"hi\"")],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_flexible_pads_rows_to_the_widest() {
+        let input = "a,b\nc\nd,\"e,1\",f\n";
+        let options = ReadOptions { flexible: true, ..ReadOptions::default() };
+        let rows = read_csv_bytes(input, &options).unwrap();
+        assert_eq!(
+            rows,
+            [
+                [text("a"), text("b"), CellValue::Null],
+                [text("c"), CellValue::Null, CellValue::Null],
+                [text("d"), text("e,1"), text("f")],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_ragged_rows_fail_without_flexible() {
+        assert!(read_csv_bytes("a,b\nc\n", &ReadOptions::default()).is_err());
+    }
+
+    #[test]
+    fn sniffs_semicolon_and_tab() {
+        assert_eq!(sniff_delimiter(b"a;b;c\n1;2;3\n"), b';');
+        assert_eq!(sniff_delimiter(b"a\tb\tc\n1\t2\t3\n"), b'\t');
+        assert_eq!(sniff_delimiter(b"a,b\n1,2\n"), b',');
+        // Commas inside quotes are not counted
+        assert_eq!(sniff_delimiter(b"\"x, y, z\";1\n\"a,b\";2\n"), b';');
+        assert_eq!(sniff_delimiter(b"\"x, y, z\"\t1\n\"a,b\"\t2\n"), b'\t');
+    }
+
+    #[test]
+    fn sniffed_semicolon_keeps_quoted_commas() {
+        let input = "name;note\n\"Smith, J\";\"a, b, c\"\nx;y\n";
+        let file_data = read_bytes(input.as_bytes(), FileFormat::Csv, "test.csv", &ReadOptions::default()).unwrap();
+        assert_eq!(file_data.delimiter, Some(';'));
+        assert_eq!(
+            file_data.sheets[0].rows,
+            [[text("name"), text("note")], [text("Smith, J"), text("a, b, c")], [text("x"), text("y")]]
+        );
+    }
+
+    #[test]
+    fn fixed_width_short_lines_yield_null() {
+        let options = ReadOptions::default();
+        assert_eq!(
+            split_fixed_width("ab   12", &[5, 3, 4], &options),
+            [text("ab"), CellValue::Integer(12), CellValue::Null]
+        );
+        assert_eq!(split_fixed_width("", &[2, 2], &options), [CellValue::Null, CellValue::Null]);
+        // Widths count characters; text past the last column is dropped
+        assert_eq!(split_fixed_width("东京x  yz", &[3, 3], &options), [text("东京x"), text("y")]);
+    }
+
+    #[test]
+    fn read_fixed_width_pads_short_lines() {
+        let path = std::env::temp_dir().join(format!("simple-table-fixed-{}.txt", std::process::id()));
+        std::fs::write(&path, "id  name\r\n1   Ann\n22\n").unwrap();
+        let read = read_fixed_width(&path, &[4, 4], &ReadOptions::default(), &|_| {}, &AtomicBool::new(false));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            read.unwrap().sheets[0].rows,
+            [
+                [text("id"), text("name")],
+                [CellValue::Integer(1), text("Ann")],
+                [CellValue::Integer(22), CellValue::Null],
+            ]
+        );
+    }
 
-    match extension.as_str() {
-        "xlsx" | "xls" | "ods" => read_excel(path),
-        "csv" => read_csv(path),
-        _ => Err(AppError::UnsupportedFormat),
+    #[test]
+    fn defined_range_with_quoted_sheet_name() {
+        let position = |row, col| CellPosition { row, col };
+        assert_eq!(
+            parse_defined_range("'My ''Data'' Sheet'!$A$1:$C$4"),
+            Some(("My 'Data' Sheet".to_string(), position(0, 0), position(3, 2)))
+        );
+        assert_eq!(
+            parse_defined_range("=Sheet1!$B$2"),
+            Some(("Sheet1".to_string(), position(1, 1), position(1, 1)))
+        );
+        // A "!" inside the quotes belongs to the sheet name
+        assert_eq!(
+            parse_defined_range("'a!b'!A1:B2"),
+            Some(("a!b".to_string(), position(0, 0), position(1, 1)))
+        );
+        assert_eq!(parse_defined_range("NoSheet"), None);
+        assert_eq!(parse_defined_range("Sheet1!#REF!"), None);
     }
 }
@@ -1,18 +1,58 @@
+use super::progress::Progress;
 use crate::error::AppError;
-use crate::types::{CellValue, FileData};
+use crate::ops::naming::{sanitize_sheet_name, unique_sheet_name};
+use crate::types::{CellValue, CsvBooleans, FileData, FileFormat, SaveResult, WriteOptions};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::io::Write;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use xlsxwriter::*;
 
-fn write_excel(path: &Path, file_data: &FileData) -> Result<(), AppError> {
+/// Largest integer an xlsx number cell (a double) holds exactly
+const MAX_EXACT_XLSX_INTEGER: u64 = 1 << 53;
+
+/// Sheet names valid in xlsx: invalid characters removed, truncated to 31
+/// characters and made unique (case-insensitively). Each renamed sheet is
+/// reported in `warnings`.
+fn xlsx_sheet_names(file_data: &FileData, warnings: &mut Vec<String>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(file_data.sheets.len());
+    for sheet in &file_data.sheets {
+        let name = unique_sheet_name(names.iter().map(String::as_str), &sanitize_sheet_name(&sheet.name));
+        if name != sheet.name {
+            warnings.push(format!("Sheet '{}' was saved as '{}'", sheet.name, name));
+        }
+        names.push(name);
+    }
+    names
+}
+
+fn write_excel(
+    path: &Path,
+    file_data: &FileData,
+    options: &WriteOptions,
+    warnings: &mut Vec<String>,
+    progress: &Progress,
+) -> Result<(), AppError> {
+    let sheet_names = if options.sanitize_sheet_names {
+        xlsx_sheet_names(file_data, warnings)
+    } else {
+        file_data.sheets.iter().map(|s| s.name.clone()).collect()
+    };
     let path_str = path
         .to_str()
         .ok_or(AppError::WriteError("Invalid path".to_string()))?;
     let workbook =
         Workbook::new(path_str).map_err(|e| AppError::WriteError(e.to_string()))?;
 
-    for sheet in &file_data.sheets {
+    let total_rows: usize = file_data.sheets.iter().map(|s| s.rows.len()).sum();
+    let mut written_rows = 0;
+    let mut date_format = Format::new();
+    date_format.set_num_format("yyyy-mm-dd");
+
+    for (sheet, name) in file_data.sheets.iter().zip(&sheet_names) {
         let mut worksheet = workbook
-            .add_worksheet(Some(&sheet.name))
+            .add_worksheet(Some(name))
             .map_err(|e| AppError::WriteError(e.to_string()))?;
 
         // Write cells without formats (for now, formats will be added later)
@@ -20,12 +60,29 @@ fn write_excel(path: &Path, file_data: &FileData) -> Result<(), AppError> {
             for (col_idx, cell) in row.iter().enumerate() {
                 let row_u32 = row_idx as u32;
                 let col_u16 = col_idx as u16;
+                // Display text is not written back; the raw value is
+                let mut cell = cell;
+                while let CellValue::Formatted { value, .. } = cell {
+                    cell = value;
+                }
                 match cell {
                     CellValue::String(s) => {
                         worksheet
                             .write_string(row_u32, col_u16, s.as_str(), None)
                             .map_err(|e| AppError::WriteError(e.to_string()))?;
                     }
+                    CellValue::Integer(i) if i.unsigned_abs() > MAX_EXACT_XLSX_INTEGER => {
+                        // xlsx stores numbers as doubles; keep every digit of long IDs as text
+                        // (they are read back as text, with the digits unchanged)
+                        worksheet
+                            .write_string(row_u32, col_u16, &i.to_string(), None)
+                            .map_err(|e| AppError::WriteError(e.to_string()))?;
+                    }
+                    CellValue::Integer(i) => {
+                        worksheet
+                            .write_number(row_u32, col_u16, *i as f64, None)
+                            .map_err(|e| AppError::WriteError(e.to_string()))?;
+                    }
                     CellValue::Number(n) => {
                         worksheet
                             .write_number(row_u32, col_u16, *n, None)
@@ -36,13 +93,49 @@ fn write_excel(path: &Path, file_data: &FileData) -> Result<(), AppError> {
                             .write_boolean(row_u32, col_u16, *b, None)
                             .map_err(|e| AppError::WriteError(e.to_string()))?;
                     }
-                    CellValue::Null => {
+                    CellValue::Date { date } => {
+                        // Write real dates so Excel can sort and format them; keep anything unparseable as text
+                        match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                            Ok(d) => {
+                                use chrono::Datelike;
+                                let datetime = DateTime::new(d.year() as i16, d.month() as i8, d.day() as i8, 0, 0, 0.0);
+                                worksheet.write_datetime(row_u32, col_u16, &datetime, Some(&date_format))
+                            }
+                            Err(_) => worksheet.write_string(row_u32, col_u16, date, None),
+                        }
+                        .map_err(|e| AppError::WriteError(e.to_string()))?;
+                    }
+                    // Formatted cells were unwrapped above
+                    CellValue::Null | CellValue::Formatted { .. } => {
                         worksheet
                             .write_blank(row_u32, col_u16, None)
                             .map_err(|e| AppError::WriteError(e.to_string()))?;
                     }
+                    CellValue::Formula { expr, cached } => {
+                        // Keep the cached result so viewers that don't recalculate still show it
+                        match cached.cached_value().as_f64() {
+                            Some(n) => worksheet.write_formula_num(row_u32, col_u16, expr, None, n),
+                            None => worksheet.write_formula(row_u32, col_u16, expr, None),
+                        }
+                        .map_err(|e| AppError::WriteError(e.to_string()))?;
+                    }
                 }
             }
+            written_rows += 1;
+            progress.report(written_rows, total_rows);
+        }
+
+        // Apply custom row heights; rows without one keep Excel's default
+        for (row_idx, height) in sheet.row_heights.iter().enumerate() {
+            if let Some(height) = height {
+                worksheet
+                    .set_row(row_idx as u32, *height, None)
+                    .map_err(|e| AppError::WriteError(e.to_string()))?;
+            }
+        }
+
+        if let Some(freeze) = sheet.freeze {
+            worksheet.freeze_panes(freeze.rows, freeze.cols);
         }
 
         // Write merged cells
@@ -55,12 +148,7 @@ fn write_excel(path: &Path, file_data: &FileData) -> Result<(), AppError> {
                 .unwrap_or(CellValue::Null);
 
             // Convert value to string for merge_range (xlsxwriter only supports strings)
-            let s = match value {
-                CellValue::String(s) => s,
-                CellValue::Number(n) => n.to_string(),
-                CellValue::Boolean(b) => b.to_string(),
-                CellValue::Null => String::new(),
-            };
+            let s = value.to_text();
 
             worksheet
                 .merge_range(
@@ -81,24 +169,48 @@ fn write_excel(path: &Path, file_data: &FileData) -> Result<(), AppError> {
     Ok(())
 }
 
-fn write_csv(path: &Path, file_data: &FileData) -> Result<(), AppError> {
-    let mut writer =
-        csv::Writer::from_path(path).map_err(|e| AppError::WriteError(e.to_string()))?;
+fn write_csv(path: &Path, file_data: &FileData, options: &WriteOptions, progress: &Progress) -> Result<(), AppError> {
+    let delimiter = options.delimiter.or_else(|| FileFormat::default_delimiter(path));
+    let file = std::fs::File::create(path).map_err(|e| AppError::WriteError(e.to_string()))?;
+    write_csv_to(file, file_data, delimiter, options, progress)
+}
+
+/// Text of a cell in CSV, rendering booleans as chosen by `booleans`
+fn csv_field(cell: &CellValue, booleans: CsvBooleans) -> Cow<'_, str> {
+    match (cell.cached_value(), booleans) {
+        (CellValue::Boolean(b), CsvBooleans::OneZero) => Cow::Borrowed(if *b { "1" } else { "0" }),
+        _ => cell.to_text(),
+    }
+}
+
+/// Write the first sheet as CSV to any writer, separating fields with
+/// `delimiter` (a comma when `None`)
+fn write_csv_to<W: Write>(
+    out: W,
+    file_data: &FileData,
+    delimiter: Option<char>,
+    options: &WriteOptions,
+    progress: &Progress,
+) -> Result<(), AppError> {
+    let delimiter = match delimiter.unwrap_or(',') {
+        c if c.is_ascii() => c as u8,
+        c => {
+            return Err(AppError::WriteError(format!(
+                "Delimiter '{}' must be a single ASCII character",
+                c
+            )))
+        }
+    };
+    let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(out);
 
     if let Some(first_sheet) = file_data.sheets.first() {
-        for row in &first_sheet.rows {
-            let string_row: Vec<String> = row
-                .iter()
-                .map(|cell| match cell {
-                    CellValue::String(s) => s.clone(),
-                    CellValue::Number(n) => n.to_string(),
-                    CellValue::Boolean(b) => b.to_string(),
-                    CellValue::Null => String::new(),
-                })
-                .collect();
+        let total_rows = first_sheet.rows.len();
+        for (row_idx, row) in first_sheet.rows.iter().enumerate() {
+            // CSV has no formulas, so their cached results are written
             writer
-                .write_record(&string_row)
+                .write_record(row.iter().map(|cell| csv_field(cell, options.csv_booleans).into_owned()))
                 .map_err(|e| AppError::WriteError(e.to_string()))?;
+            progress.report(row_idx + 1, total_rows);
         }
     }
 
@@ -108,16 +220,273 @@ fn write_csv(path: &Path, file_data: &FileData) -> Result<(), AppError> {
     Ok(())
 }
 
-pub fn save_file(path: &Path, file_data: &FileData) -> Result<(), AppError> {
-    let extension = path
+/// Save a file, reporting 0-100 progress through `on_progress`.
+/// The format comes from `options.format` or, failing that, the extension.
+/// Returns the path actually written, which differs from `path` when the
+/// format had to be downgraded (e.g. `.xlsm` is written as `.xlsx`).
+pub fn save_file(
+    path: &Path,
+    file_data: &FileData,
+    options: &WriteOptions,
+    on_progress: &dyn Fn(u8),
+) -> Result<SaveResult, AppError> {
+    let progress = Progress::new(on_progress);
+    let mut warnings = Vec::new();
+    let format = FileFormat::resolve(options.format, path)
+        .filter(|f| f.can_write())
+        .ok_or(AppError::UnsupportedFormat)?;
+    let is_xlsm = path
         .extension()
         .and_then(|e| e.to_str())
-        .map(|e| e.to_lowercase())
-        .ok_or(AppError::UnsupportedFormat)?;
+        .is_some_and(|e| e.eq_ignore_ascii_case("xlsm"));
+
+    let path = match format {
+        // xlsxwriter cannot write VBA projects, and Excel rejects a .xlsm without one
+        FileFormat::Xlsx if is_xlsm => {
+            let xlsx_path = path.with_extension("xlsx");
+            write_excel(&xlsx_path, file_data, options, &mut warnings, &progress)?;
+            warnings.push(format!(
+                "Macros cannot be preserved; saved as {} instead of .xlsm",
+                xlsx_path.display()
+            ));
+            xlsx_path
+        }
+        FileFormat::Xlsx => {
+            write_excel(path, file_data, options, &mut warnings, &progress)?;
+            path.to_path_buf()
+        }
+        FileFormat::Csv => {
+            write_csv(path, file_data, options, &progress)?;
+            path.to_path_buf()
+        }
+        FileFormat::Xls | FileFormat::Ods | FileFormat::Numbers => return Err(AppError::UnsupportedFormat),
+    };
+
+    let sha256 = if options.checksum { Some(sha256_file(&path)?) } else { None };
+
+    progress.finish();
+    Ok(SaveResult {
+        path: path.to_string_lossy().into_owned(),
+        warnings,
+        sha256,
+    })
+}
+
+/// SHA-256 of a file on disk as lowercase hex. Hashing the file after the
+/// write (rather than the in-memory data) covers exactly the bytes stored.
+fn sha256_file(path: &Path) -> Result<String, AppError> {
+    let mut file = std::fs::File::open(path).map_err(|e| AppError::WriteError(e.to_string()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| AppError::WriteError(e.to_string()))?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Serialize a workbook into memory instead of a file.
+/// CSV is written directly into the buffer; xlsxwriter can only write to a
+/// path, so xlsx goes through a temporary file that is removed afterwards.
+pub fn write_bytes(file_data: &FileData, format: FileFormat, options: &WriteOptions) -> Result<Vec<u8>, AppError> {
+    let progress = Progress::new(&|_| {});
+    match format {
+        FileFormat::Csv => {
+            let mut buffer = Vec::new();
+            write_csv_to(&mut buffer, file_data, options.delimiter, options, &progress)?;
+            Ok(buffer)
+        }
+        FileFormat::Xlsx => {
+            static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let temp_path = std::env::temp_dir().join(format!(
+                "simple-table-{}-{}.xlsx",
+                std::process::id(),
+                TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            // There is no result to report renamed sheets in, so they are renamed silently
+            let result = write_excel(&temp_path, file_data, options, &mut Vec::new(), &progress)
+                .and_then(|_| std::fs::read(&temp_path).map_err(|e| AppError::WriteError(e.to_string())));
+            let _ = std::fs::remove_file(&temp_path);
+            result
+        }
+        FileFormat::Xls | FileFormat::Ods | FileFormat::Numbers => Err(AppError::UnsupportedFormat),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::reader::{read_bytes, read_file};
+    use crate::ops::test_util::{file_data, search, state_with, text};
+    use crate::types::ReadOptions;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicBool;
+
+    /// A path in the temp directory that is removed when dropped
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(extension: &str) -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            Self(std::env::temp_dir().join(format!(
+                "simple-table-test-{}-{}.{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                extension
+            )))
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// One sheet mixing every plain cell type: nulls, negatives, unicode
+    /// text, booleans, integers and non-integer numbers
+    fn mixed_sheet() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![text("name"), text("amount"), text("flag")],
+            vec![text("Zoë 東京 🚀"), CellValue::Integer(-42), CellValue::Boolean(true)],
+            vec![CellValue::Null, CellValue::Number(-2.5), CellValue::Boolean(false)],
+            vec![text("007"), CellValue::Integer(9_007_199_254_740_993), CellValue::Null],
+            vec![text("x, \"quoted\"\nline"), CellValue::Number(3.0), CellValue::Number(0.1)],
+        ]
+    }
+
+    /// `mixed_sheet` after the documented lossy conversions: a whole-valued
+    /// `Number` is read back as `Integer`, and in xlsx an integer beyond 2^53
+    /// is stored as text and read back as text with the same digits
+    fn expected_sheet(format: FileFormat) -> Vec<Vec<CellValue>> {
+        let mut rows = mixed_sheet();
+        rows[4][1] = CellValue::Integer(3);
+        if format == FileFormat::Xlsx {
+            rows[3][1] = text("9007199254740993");
+        }
+        rows
+    }
+
+    fn save_and_read(format: FileFormat, extension: &str) -> FileData {
+        let path = TempPath::new(extension);
+        let options = WriteOptions { format: Some(format), ..WriteOptions::default() };
+        save_file(&path.0, &file_data(vec![mixed_sheet()]), &options, &|_| {}).unwrap();
+        read_file(&path.0, &ReadOptions::default(), &|_| {}, &AtomicBool::new(false)).unwrap()
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let read = save_and_read(FileFormat::Csv, "csv");
+        assert_eq!(read.sheets.len(), 1);
+        assert_eq!(read.sheets[0].rows, expected_sheet(FileFormat::Csv));
+    }
+
+    #[test]
+    fn csv_round_trip_keeps_only_the_first_sheet() {
+        let data = file_data(vec![mixed_sheet(), vec![vec![text("second")]]]);
+        let bytes = write_bytes(&data, FileFormat::Csv, &WriteOptions::default()).unwrap();
+        let read = read_bytes(&bytes, FileFormat::Csv, "test.csv", &ReadOptions::default()).unwrap();
+        assert_eq!(read.sheets.len(), 1);
+        assert_eq!(read.sheets[0].rows, expected_sheet(FileFormat::Csv));
+    }
+
+    #[test]
+    fn xlsx_round_trip() {
+        let read = save_and_read(FileFormat::Xlsx, "xlsx");
+        assert_eq!(read.sheets.len(), 1);
+        assert_eq!(read.sheets[0].name, "Sheet1");
+        assert_eq!(read.sheets[0].rows, expected_sheet(FileFormat::Xlsx));
+    }
+
+    #[test]
+    fn xlsx_round_trip_through_bytes() {
+        let data = file_data(vec![mixed_sheet(), vec![vec![text("second"), CellValue::Integer(2)]]]);
+        let bytes = write_bytes(&data, FileFormat::Xlsx, &WriteOptions::default()).unwrap();
+        let read = read_bytes(&bytes, FileFormat::Xlsx, "test.xlsx", &ReadOptions::default()).unwrap();
+        let names: Vec<&str> = read.sheets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Sheet1", "Sheet2"]);
+        assert_eq!(read.sheets[0].rows, expected_sheet(FileFormat::Xlsx));
+        assert_eq!(read.sheets[1].rows, vec![vec![text("second"), CellValue::Integer(2)]]);
+    }
+
+    #[test]
+    fn xlsx_text_with_leading_zeros_survives_read_search_and_write() {
+        let options = WriteOptions::default();
+        let original = file_data(vec![vec![vec![text("007"), text("0.50"), CellValue::Integer(7)]]]);
+        let bytes = write_bytes(&original, FileFormat::Xlsx, &options).unwrap();
+        let read = read_bytes(&bytes, FileFormat::Xlsx, "test.xlsx", &ReadOptions::default()).unwrap();
+        assert_eq!(read.sheets[0].rows, original.sheets[0].rows);
+
+        let state = state_with(vec![read.sheets[0].rows.clone()]);
+        // A numeric query also matches numbers by value, but text only by its exact text
+        assert_eq!(search(&state, 0, "007"), vec![(0, 0), (0, 2)]);
+        assert_eq!(search(&state, 0, "7"), vec![(0, 2)]);
+        assert_eq!(search(&state, 0, "0.50"), vec![(0, 1)]);
+
+        let saved = state.read().unwrap().as_ref().unwrap().file_data.clone();
+        let bytes = write_bytes(&saved, FileFormat::Xlsx, &options).unwrap();
+        let reread = read_bytes(&bytes, FileFormat::Xlsx, "test.xlsx", &ReadOptions::default()).unwrap();
+        assert_eq!(reread.sheets[0].rows, original.sheets[0].rows);
+    }
+
+    #[test]
+    fn xlsx_numeric_text_stays_text_after_read_and_json() {
+        let original = file_data(vec![vec![vec![text("1234567890123456789"), text("9007199254740993")]]]);
+        let bytes = write_bytes(&original, FileFormat::Xlsx, &WriteOptions::default()).unwrap();
+        let read = read_bytes(&bytes, FileFormat::Xlsx, "test.xlsx", &ReadOptions::default()).unwrap();
+        assert_eq!(read.sheets[0].rows, original.sheets[0].rows);
+
+        let json: FileData = serde_json::from_str(&serde_json::to_string(&read).unwrap()).unwrap();
+        assert_eq!(json.sheets[0].rows, original.sheets[0].rows);
+    }
+
+    /// Write a boolean column and an integer 1/0 column as CSV and read them
+    /// back, both using `booleans`
+    fn csv_boolean_round_trip(booleans: CsvBooleans) -> (String, Vec<Vec<CellValue>>) {
+        let data = file_data(vec![vec![
+            vec![CellValue::Boolean(true), CellValue::Integer(1)],
+            vec![CellValue::Boolean(false), CellValue::Integer(0)],
+        ]]);
+        let write_options = WriteOptions { csv_booleans: booleans, ..WriteOptions::default() };
+        let bytes = write_bytes(&data, FileFormat::Csv, &write_options).unwrap();
+        let read_options = ReadOptions { csv_booleans: booleans, ..ReadOptions::default() };
+        let read = read_bytes(&bytes, FileFormat::Csv, "test.csv", &read_options).unwrap();
+        (String::from_utf8(bytes).unwrap(), read.sheets[0].rows.clone())
+    }
+
+    #[test]
+    fn csv_booleans_as_true_false() {
+        let (csv, rows) = csv_boolean_round_trip(CsvBooleans::TrueFalse);
+        assert_eq!(csv, "true,1\nfalse,0\n");
+        assert_eq!(
+            rows,
+            [
+                [CellValue::Boolean(true), CellValue::Integer(1)],
+                [CellValue::Boolean(false), CellValue::Integer(0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_booleans_as_one_zero() {
+        let (csv, rows) = csv_boolean_round_trip(CsvBooleans::OneZero);
+        assert_eq!(csv, "1,1\n0,0\n");
+        // Booleans come back as booleans, and so do integers that were 1/0
+        assert_eq!(
+            rows,
+            [
+                [CellValue::Boolean(true), CellValue::Boolean(true)],
+                [CellValue::Boolean(false), CellValue::Boolean(false)],
+            ]
+        );
+    }
 
-    match extension.as_str() {
-        "xlsx" => write_excel(path, file_data),
-        "csv" => write_csv(path, file_data),
-        _ => Err(AppError::UnsupportedFormat),
+    #[test]
+    fn csv_booleans_read_with_the_other_option() {
+        let one_zero = b"1,true\n0,false\n";
+        let read = read_bytes(one_zero, FileFormat::Csv, "test.csv", &ReadOptions::default()).unwrap();
+        assert_eq!(
+            read.sheets[0].rows,
+            [
+                [CellValue::Integer(1), CellValue::Boolean(true)],
+                [CellValue::Integer(0), CellValue::Boolean(false)],
+            ]
+        );
     }
 }
@@ -6,8 +6,19 @@ mod state;
 mod types;
 
 use commands::{
-    add_column, add_row, add_sheet, delete_column, delete_row, delete_sheet, get_default_save_path,
-    get_editor_state, get_file_data, init_file, read_file, redo, save_file, search, set_cell, sort_column, undo,
+    add_column, add_row, add_row_with_values, add_sheet, add_sheet_named, add_sheet_sized,
+    cancel_read, clear_freeze, content_hash, copy_range_tsv, delete_column, delete_row,
+    delete_sheet, distinct_values, duplicate_column, duplicate_row, export_bytes, export_operations,
+    find_blank_like, find_duplicates, get_cell, get_cell_a1, get_cell_formula,
+    get_default_save_path, get_editor_state, get_file_data, get_file_data_compact, get_range_a1,
+    group_by, import_directory, import_operations, index_stats, init_file, insert_row_above,
+    insert_row_below, is_dirty, move_column, move_row, move_sheet, new_file, normalize_newlines,
+    pivot, profile_sheet, read_csv_rows, read_file, read_file_bytes, read_files, read_fixed_width,
+    read_named_range, redo, remove_duplicates, remove_empty_rows, replace_all, replace_in_range,
+    resize_sheet, resolve_sheet, row_counts, save_file, search, search_begin, search_end,
+    search_next, search_prev, search_summary, set_cell, set_default_cell, set_default_sheet_size,
+    set_freeze, set_has_header, set_size_limits, set_sync_index, set_tokenizer, sort_column,
+    split_sheet_by_column, suggest, undo, validate_column, validate_workbook,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -16,24 +27,89 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            commands::init_operation_events(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_file,
+            read_files,
+            import_directory,
+            read_fixed_width,
+            read_file_bytes,
+            export_bytes,
+            read_csv_rows,
+            read_named_range,
+            cancel_read,
             save_file,
             get_default_save_path,
             init_file,
+            new_file,
             get_file_data,
+            get_file_data_compact,
+            content_hash,
+            is_dirty,
+            get_range_a1,
             undo,
             redo,
             set_cell,
             add_row,
+            insert_row_above,
+            insert_row_below,
+            duplicate_row,
+            add_row_with_values,
             delete_row,
             add_column,
+            duplicate_column,
             delete_column,
+            move_row,
+            move_column,
             add_sheet,
+            add_sheet_sized,
+            add_sheet_named,
+            set_default_sheet_size,
+            set_default_cell,
+            set_size_limits,
             delete_sheet,
+            move_sheet,
+            resize_sheet,
+            remove_duplicates,
+            remove_empty_rows,
+            resolve_sheet,
+            set_has_header,
+            set_freeze,
+            clear_freeze,
+            split_sheet_by_column,
+            group_by,
+            index_stats,
+            pivot,
             sort_column,
             get_editor_state,
-            search
+            set_sync_index,
+            set_tokenizer,
+            export_operations,
+            import_operations,
+            search,
+            search_begin,
+            search_next,
+            search_prev,
+            search_end,
+            search_summary,
+            suggest,
+            replace_all,
+            replace_in_range,
+            normalize_newlines,
+            copy_range_tsv,
+            validate_column,
+            find_duplicates,
+            find_blank_like,
+            validate_workbook,
+            get_cell,
+            get_cell_formula,
+            get_cell_a1,
+            profile_sheet,
+            distinct_values,
+            row_counts
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -4,3 +4,13 @@ pub mod search_ops;
 pub mod editor_ops;
 pub mod cell_ops;
 pub mod sort_ops;
+pub mod bounds;
+pub mod validation_ops;
+pub mod analysis_ops;
+pub mod naming;
+pub mod sheet_ops;
+pub mod replace_ops;
+pub mod clipboard_ops;
+pub mod a1;
+#[cfg(test)]
+pub mod test_util;
@@ -0,0 +1,69 @@
+use crate::types::CellPosition;
+
+/// 将列索引转换为字母 (0 -> A, 1 -> B, ..., 26 -> AA)
+pub fn col_to_letter(col: usize) -> String {
+    let mut result = String::new();
+    let mut n = col;
+    while n >= 26 {
+        result.insert(0, char::from_u32((n % 26) as u32 + 65).unwrap());
+        n = n / 26 - 1;
+    }
+    result.insert(0, char::from_u32(n as u32 + 65).unwrap());
+    result
+}
+
+/// 将列字母转换为列索引 (A -> 0, Z -> 25, AA -> 26)，不区分大小写
+pub fn letter_to_col(letters: &str) -> Option<usize> {
+    if letters.is_empty() {
+        return None;
+    }
+    let mut col: usize = 0;
+    for c in letters.chars() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        let digit = (c.to_ascii_uppercase() as u8 - b'A') as usize + 1;
+        col = col.checked_mul(26)?.checked_add(digit)?;
+    }
+    Some(col - 1)
+}
+
+/// 解析 A1 引用（如 "B12"、"$B$12"，不区分大小写），返回 0 基的位置
+/// 格式不正确或行号为 0 时返回 None
+pub fn parse_a1(reference: &str) -> Option<CellPosition> {
+    let reference = reference.trim();
+    let rest = reference.strip_prefix('$').unwrap_or(reference);
+    let split = rest.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (letters, digits) = rest.split_at(split);
+    let digits = digits.strip_prefix('$').unwrap_or(digits);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let col = letter_to_col(letters)?;
+    let row = digits.parse::<usize>().ok()?.checked_sub(1)?;
+    Some(CellPosition { row, col })
+}
+
+/// 解析区域引用（如 "A1:C3"，单个单元格 "B2" 视为 1×1 区域）
+/// 反向区域（如 "C3:A1"）会规范化，返回 (左上角, 右下角)
+pub fn parse_a1_range(reference: &str) -> Option<(CellPosition, CellPosition)> {
+    let (start, end) = match reference.split_once(':') {
+        Some((start, end)) => (parse_a1(start)?, parse_a1(end)?),
+        None => {
+            let pos = parse_a1(reference)?;
+            (pos.clone(), pos)
+        }
+    };
+
+    Some((
+        CellPosition {
+            row: start.row.min(end.row),
+            col: start.col.min(end.col),
+        },
+        CellPosition {
+            row: start.row.max(end.row),
+            col: start.col.max(end.col),
+        },
+    ))
+}
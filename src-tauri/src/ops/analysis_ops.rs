@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::error::AppError;
+use crate::ops::bounds::{check_sheet, Bounds};
+use crate::state::editor_state::EditorState;
+use crate::types::{CellKey, CellValue, ColumnProfile, RowCount, ValueCount};
+
+/// 统计每个 sheet 的总行数和空行数（按 sheet 顺序，只读）
+pub fn do_row_counts(state: Arc<RwLock<Option<EditorState>>>) -> Result<Vec<RowCount>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    Ok(editor_state
+        .file_data
+        .sheets
+        .iter()
+        .map(|sheet| RowCount {
+            total_rows: sheet.rows.len(),
+            empty_rows: sheet
+                .rows
+                .iter()
+                .filter(|row| row.iter().all(|c| matches!(c, CellValue::Null)))
+                .count(),
+        })
+        .collect())
+}
+
+/// 统计每列的填充情况和值类型分布（只读）
+pub fn do_profile_sheet(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+) -> Result<Vec<ColumnProfile>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let mut profiles = vec![ColumnProfile::default(); Bounds::of(sheet).cols];
+
+    for row in &sheet.rows {
+        for (col, profile) in profiles.iter_mut().enumerate() {
+            match row.get(col).map(CellValue::cached_value) {
+                None | Some(CellValue::Null) => profile.empty += 1,
+                Some(CellValue::String(s)) if s.is_empty() => profile.empty += 1,
+                Some(CellValue::String(_)) => {
+                    profile.filled += 1;
+                    profile.text += 1;
+                }
+                Some(CellValue::Integer(_) | CellValue::Number(_)) => {
+                    profile.filled += 1;
+                    profile.numeric += 1;
+                }
+                Some(CellValue::Boolean(_)) => {
+                    profile.filled += 1;
+                    profile.boolean += 1;
+                }
+                Some(CellValue::Date { .. }) => {
+                    profile.filled += 1;
+                    profile.date += 1;
+                }
+                Some(CellValue::Formula { .. } | CellValue::Formatted { .. }) => profile.filled += 1,
+            }
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// 统计列中的不重复值及出现次数（按首次出现顺序，忽略表头）
+/// include_null 为 true 时，Null 和不规则行中缺失的单元格计为一个 Null 值
+pub fn do_distinct_values(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col: usize,
+    include_null: bool,
+) -> Result<Vec<ValueCount>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_col(col)?;
+
+    let mut seen: HashMap<CellKey, usize> = HashMap::new();
+    let mut values: Vec<ValueCount> = Vec::new();
+
+    for row in sheet.rows.iter().skip(sheet.data_start()) {
+        let cell = row.get(col).unwrap_or(&CellValue::Null);
+        if matches!(cell, CellValue::Null) && !include_null {
+            continue;
+        }
+
+        match seen.get(&CellKey(cell)) {
+            Some(&i) => values[i].count += 1,
+            None => {
+                seen.insert(CellKey(cell), values.len());
+                values.push(ValueCount { value: cell.clone(), count: 1 });
+            }
+        }
+    }
+
+    Ok(values)
+}
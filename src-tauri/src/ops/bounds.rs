@@ -0,0 +1,115 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::AppError;
+use crate::types::{FileData, SheetData};
+
+/// xlsx 工作表的最大行数
+pub const XLSX_MAX_ROWS: usize = 1_048_576;
+/// xlsx 工作表的最大列数
+pub const XLSX_MAX_COLS: usize = 16_384;
+
+/// 单个 sheet 的最大行数（默认与 xlsx 上限一致）
+static MAX_ROWS: AtomicUsize = AtomicUsize::new(XLSX_MAX_ROWS);
+/// 单个 sheet 的最大列数（默认与 xlsx 上限一致）
+static MAX_COLS: AtomicUsize = AtomicUsize::new(XLSX_MAX_COLS);
+/// 单个 sheet 的最大单元格数（行数 × 列数），防止误操作分配过多内存
+static MAX_CELLS: AtomicUsize = AtomicUsize::new(10_000_000);
+
+/// 设置 sheet 大小上限（均需大于 0）
+pub fn set_size_limits(max_rows: usize, max_cols: usize, max_cells: usize) -> Result<(), AppError> {
+    if max_rows == 0 || max_cols == 0 || max_cells == 0 {
+        return Err(AppError::Internal("Size limits must be greater than 0".to_string()));
+    }
+    MAX_ROWS.store(max_rows, Ordering::Relaxed);
+    MAX_COLS.store(max_cols, Ordering::Relaxed);
+    MAX_CELLS.store(max_cells, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 校验 sheet 增长后的大小是否超过上限
+pub fn check_size_limit(rows: usize, cols: usize) -> Result<(), AppError> {
+    let (max_rows, max_cols, max_cells) = (
+        MAX_ROWS.load(Ordering::Relaxed),
+        MAX_COLS.load(Ordering::Relaxed),
+        MAX_CELLS.load(Ordering::Relaxed),
+    );
+    if rows > max_rows || cols > max_cols || rows.saturating_mul(cols) > max_cells {
+        return Err(AppError::Internal(format!(
+            "requested size exceeds limit: {}x{} (max {} rows, {} columns, {} cells)",
+            rows, cols, max_rows, max_cols, max_cells
+        )));
+    }
+    Ok(())
+}
+
+/// 校验 sheet 索引，返回对应的 sheet
+pub fn check_sheet(file_data: &FileData, sheet_index: usize) -> Result<&SheetData, AppError> {
+    file_data.sheets.get(sheet_index).ok_or(AppError::OutOfRange {
+        what: "Sheet",
+        index: sheet_index,
+        len: file_data.sheets.len(),
+    })
+}
+
+/// 校验新建 sheet 的大小（至少 1×1，且不超过大小上限）
+pub fn check_sheet_size((rows, cols): (usize, usize)) -> Result<(usize, usize), AppError> {
+    if rows == 0 || cols == 0 {
+        return Err(AppError::Internal(format!("Invalid sheet size {}x{}", rows, cols)));
+    }
+    check_size_limit(rows, cols)?;
+    Ok((rows, cols))
+}
+
+/// 移动操作（MoveRow/MoveColumn/MoveSheet）的目标位置：to 为移动后的位置，超出末尾时夹到最后一个位置，
+/// 前端拖拽计算略有偏差时不会越界。返回 None 表示移动没有效果（只有一项，或目标就是原位置），
+/// 调用方应作为空操作处理，不记录到撤销历史。from 须已校验在 len 范围内
+pub fn clamp_move_target(from: usize, to: usize, len: usize) -> Option<usize> {
+    let to = to.min(len.saturating_sub(1));
+    (len > 1 && to != from).then_some(to)
+}
+
+/// Sheet 的行列边界（用于在执行操作前校验前端传入的索引）
+#[derive(Clone, Copy, Debug)]
+pub struct Bounds {
+    pub rows: usize,
+    /// 最宽一行的列数（兼容不规则行）
+    pub cols: usize,
+}
+
+impl Bounds {
+    pub fn of(sheet: &SheetData) -> Self {
+        Self {
+            rows: sheet.rows.len(),
+            cols: sheet.rows.iter().map(|r| r.len()).max().unwrap_or(0),
+        }
+    }
+
+    /// 校验已有行
+    pub fn check_row(&self, row: usize) -> Result<(), AppError> {
+        check_index("Row", row, self.rows)
+    }
+
+    /// 校验插入位置（允许插入到末尾，即 row == rows）
+    pub fn check_insert_row(&self, row: usize) -> Result<(), AppError> {
+        check_index("Row", row, self.rows + 1)
+    }
+
+    /// 校验已有列
+    pub fn check_col(&self, col: usize) -> Result<(), AppError> {
+        check_index("Column", col, self.cols)
+    }
+
+    /// 校验单元格（列按该行的实际长度校验）
+    pub fn check_cell(&self, sheet: &SheetData, row: usize, col: usize) -> Result<(), AppError> {
+        self.check_row(row)?;
+        check_index("Column", col, sheet.rows[row].len())
+    }
+}
+
+fn check_index(what: &'static str, index: usize, len: usize) -> Result<(), AppError> {
+    if index < len {
+        Ok(())
+    } else {
+        Err(AppError::OutOfRange { what, index, len })
+    }
+}
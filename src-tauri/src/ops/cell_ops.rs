@@ -1,12 +1,85 @@
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use crate::ops::a1::{parse_a1, parse_a1_range};
+use crate::ops::bounds::{check_sheet, check_sheet_size, check_size_limit, clamp_move_target, Bounds};
 use crate::ops::index_ops::spawn_rebuild_sheet_index;
+use crate::ops::naming::validate_sheet_name;
 use crate::error::AppError;
 use crate::state::editor_state::{EditorState, Operation};
-use crate::types::{CellValue, SheetData};
+use crate::types::{CellValue, OperationResult, SheetData};
 
-/// 设置单元格值
+/// 获取单元格值
+pub fn do_get_cell(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row: usize,
+    col: usize,
+) -> Result<CellValue, AppError> {
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => {
+            let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+            Bounds::of(sheet).check_cell(sheet, row, col)?;
+            Ok(sheet.rows[row][col].clone())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 获取单元格的公式表达式（如 "=SUM(A1:A3)"），非公式单元格返回 None
+pub fn do_get_cell_formula(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row: usize,
+    col: usize,
+) -> Result<Option<String>, AppError> {
+    match do_get_cell(state, sheet_index, row, col)? {
+        CellValue::Formula { expr, .. } => Ok(Some(expr)),
+        _ => Ok(None),
+    }
+}
+
+/// 按 A1 引用（如 "B12"）获取单元格值
+pub fn do_get_cell_a1(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    reference: String,
+) -> Result<CellValue, AppError> {
+    let pos = parse_a1(&reference).ok_or(AppError::InvalidReference(reference))?;
+    do_get_cell(state, sheet_index, pos.row, pos.col)
+}
+
+/// 按 A1 区域引用（如 "A1:C3"，起止顺序不限）获取矩形区域的单元格值
+/// 区域须在 sheet 范围内，不规则行中缺失的单元格返回 Null
+pub fn do_get_range_a1(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    reference: String,
+) -> Result<Vec<Vec<CellValue>>, AppError> {
+    let (start, end) = parse_a1_range(&reference).ok_or(AppError::InvalidReference(reference))?;
+
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => {
+            let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+            let bounds = Bounds::of(sheet);
+            bounds.check_row(end.row)?;
+            bounds.check_col(end.col)?;
+            Ok(sheet.rows[start.row..=end.row]
+                .iter()
+                .map(|row| {
+                    (start.col..=end.col)
+                        .map(|col| row.get(col).cloned().unwrap_or(CellValue::Null))
+                        .collect()
+                })
+                .collect())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 设置单元格值，返回是否记录到撤销历史（新值与当前值相同时为 false）
 pub fn do_set_cell(
     state: Arc<RwLock<Option<EditorState>>>,
     sheet_index: usize,
@@ -14,10 +87,12 @@ pub fn do_set_cell(
     col: usize,
     old_value: CellValue,
     new_value: CellValue,
-) -> Result<(), AppError> {
+) -> Result<bool, AppError> {
     let mut state = state.write().unwrap();
     match state.as_mut() {
         Some(editor_state) => {
+            let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+            Bounds::of(sheet).check_cell(sheet, row, col)?;
             let operation = Operation::SetCell {
                 sheet_index,
                 row,
@@ -25,24 +100,123 @@ pub fn do_set_cell(
                 old_value,
                 new_value,
             };
-            editor_state.execute(operation);
-            Ok(())
+            match editor_state.execute(operation) {
+                OperationResult::SetCell { recorded, .. } => Ok(recorded),
+                _ => Ok(true),
+            }
         }
         None => Err(AppError::Internal("No file loaded".to_string())),
     }
 }
 
-/// 添加行
+/// 添加行（索引在 execute 中增量更新）
 pub fn do_add_row(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize, row_index: usize) -> Result<(), AppError> {
+    do_add_row_with_values(state, sheet_index, row_index, vec![])
+}
+
+/// 添加带数据的行，values 会按 sheet 列数补齐或截断（索引在 execute 中增量更新）
+/// 空 sheet 没有列数可参照：空 values 时插入单格的空行，避免产生零宽行
+pub fn do_add_row_with_values(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row_index: usize,
+    values: Vec<CellValue>,
+) -> Result<(), AppError> {
+    let mut state_guard = state.write().unwrap();
+    match state_guard.as_mut() {
+        Some(editor_state) => insert_row(editor_state, sheet_index, row_index, values, None).map(|_| ()),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 在选中行的上方（below 为 false）或下方插入空行，返回操作结果
+/// 空 sheet 忽略 row，插入单格的空行
+pub fn do_insert_row(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row: usize,
+    below: bool,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let row_index = if sheet.rows.is_empty() {
+        0
+    } else {
+        Bounds::of(sheet).check_row(row)?;
+        if below { row + 1 } else { row }
+    };
+    insert_row(editor_state, sheet_index, row_index, vec![], None)
+}
+
+/// 在行的下方插入该行的副本（连同行高），一次撤销即可移除，返回操作结果
+pub fn do_duplicate_row(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row_index: usize,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_row(row_index)?;
+    let values = sheet.rows[row_index].clone();
+    let row_height = sheet.row_height(row_index);
+    insert_row(editor_state, sheet_index, row_index + 1, values, row_height)
+}
+
+/// 执行 AddRow（do_add_row_with_values、do_insert_row 和 do_duplicate_row 共用）
+fn insert_row(
+    editor_state: &mut EditorState,
+    sheet_index: usize,
+    row_index: usize,
+    mut values: Vec<CellValue>,
+    row_height: Option<f64>,
+) -> Result<OperationResult, AppError> {
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let bounds = Bounds::of(sheet);
+    bounds.check_insert_row(row_index)?;
+    if sheet.rows.is_empty() && values.is_empty() {
+        values = vec![editor_state.default_cell.clone()];
+    }
+    // 空 values 表示插入空行，由 execute 按列数补齐
+    if !values.is_empty() {
+        if let Some(width) = sheet.rows.first().map(|r| r.len()) {
+            values.resize(width, CellValue::Null);
+        }
+    }
+    check_size_limit(bounds.rows + 1, bounds.cols.max(values.len()))?;
+    let operation = Operation::AddRow {
+        sheet_index,
+        row_index,
+        row_data: values,
+        row_height,
+    };
+    Ok(editor_state.execute(operation))
+}
+
+/// 删除行
+pub fn do_delete_row(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize, row_index: usize) -> Result<(), AppError> {
     let result = {
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
-                // 直接计算 row_data（空行数据）
-                let operation = Operation::AddRow {
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                Bounds::of(sheet).check_row(row_index)?;
+                // 从文件数据中获取行数据和行高（用于撤销）
+                let row_data = sheet.rows[row_index].clone();
+                let operation = Operation::DeleteRow {
                     sheet_index,
                     row_index,
-                    row_data: vec![],
+                    row_data,
+                    row_height: sheet.row_height(row_index),
                 };
                 editor_state.execute(operation);
                 Ok(())
@@ -59,19 +233,23 @@ pub fn do_add_row(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize, r
     result
 }
 
-/// 删除行
-pub fn do_delete_row(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize, row_index: usize) -> Result<(), AppError> {
+/// 添加列
+/// 空 sheet 没有行可追加列，改为插入一个单格的行（撤销时删除该行，恢复为空 sheet）
+pub fn do_add_column(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Result<(), AppError> {
     let result = {
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
-                // 从文件数据中获取行数据（用于撤销）
-                let row_data = editor_state.file_data.sheets[sheet_index].rows[row_index].clone();
-                let operation = Operation::DeleteRow {
-                    sheet_index,
-                    row_index,
-                    row_data,
-                };
+                let bounds = Bounds::of(check_sheet(&editor_state.file_data, sheet_index)?);
+                if bounds.rows == 0 {
+                    let row_data = vec![editor_state.default_cell.clone()];
+                    let operation = Operation::AddRow { sheet_index, row_index: 0, row_data, row_height: None };
+                    editor_state.execute(operation);
+                    return Ok(());
+                }
+                check_size_limit(bounds.rows, bounds.cols + 1)?;
+                // col_index 和 col_data 会在 execute 中自动计算和保存
+                let operation = Operation::AddColumn { sheet_index, col_index: None, col_data: vec![], row_lens: vec![] };
                 editor_state.execute(operation);
                 Ok(())
             }
@@ -87,22 +265,39 @@ pub fn do_delete_row(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize
     result
 }
 
-/// 添加列
-pub fn do_add_column(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Result<(), AppError> {
+/// 在列的右侧插入该列的副本（单个 AddColumn 操作，一次撤销即可移除）
+/// 不规则行中缺少该列的行不插入副本（撤销时各行恢复原长度）
+pub fn do_duplicate_column(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col_index: usize,
+) -> Result<OperationResult, AppError> {
     let result = {
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
-                // col_index 和 col_data 会在 execute 中自动计算和保存
-                let operation = Operation::AddColumn { sheet_index, col_index: None, col_data: vec![] };
-                editor_state.execute(operation);
-                Ok(())
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                let bounds = Bounds::of(sheet);
+                bounds.check_col(col_index)?;
+                check_size_limit(bounds.rows, bounds.cols + 1)?;
+                let col_data: Vec<CellValue> = sheet
+                    .rows
+                    .iter()
+                    .map(|row| row.get(col_index).cloned().unwrap_or(CellValue::Null))
+                    .collect();
+                let operation = Operation::AddColumn {
+                    sheet_index,
+                    col_index: Some(col_index + 1),
+                    col_data,
+                    row_lens: vec![],
+                };
+                Ok(editor_state.execute(operation))
             }
             None => Err(AppError::Internal("No file loaded".to_string())),
         }
     };
 
-    // 异步重建索引
+    // 列插入使后续列的位置整体平移，异步重建索引
     if result.is_ok() {
         spawn_rebuild_sheet_index(sheet_index, state.clone());
     }
@@ -116,16 +311,20 @@ pub fn do_delete_column(state: Arc<RwLock<Option<EditorState>>>, sheet_index: us
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                Bounds::of(sheet).check_col(col_index)?;
                 // 从文件数据中获取列数据（用于撤销）
-                let col_data: Vec<CellValue> = editor_state.file_data.sheets[sheet_index]
+                let col_data: Vec<CellValue> = sheet
                     .rows
                     .iter()
                     .map(|row| row.get(col_index).cloned().unwrap_or(CellValue::Null))
                     .collect();
+                // row_lens 为空，会在 execute 中自动保存
                 let operation = Operation::DeleteColumn {
                     sheet_index,
-                    col_index,
+                    col_index: Some(col_index),
                     col_data,
+                    row_lens: vec![],
                 };
                 editor_state.execute(operation);
                 Ok(())
@@ -142,36 +341,152 @@ pub fn do_delete_column(state: Arc<RwLock<Option<EditorState>>>, sheet_index: us
     result
 }
 
-/// 添加 Sheet
-pub fn do_add_sheet(state: Arc<RwLock<Option<EditorState>>>) -> Result<(), AppError> {
+/// 移动行：from 行移到 to（to 为移动后的位置，超出末尾时移到最后一行），一次撤销即可恢复
+/// 移动没有效果时（只有一行或目标即原位置）不记录操作，返回 None
+pub fn do_move_row(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    from: usize,
+    to: usize,
+) -> Result<Option<OperationResult>, AppError> {
     let result = {
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
-                // 传入空字符串和 None，让 execute 生成名称并创建空 sheet
-                let operation = Operation::AddSheet {
-                    name: String::new(),
-                    sheet_data: None,
-                    sheet_index: None,
-                };
-                editor_state.execute(operation);
-                Ok(())
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                let bounds = Bounds::of(sheet);
+                bounds.check_row(from)?;
+                Ok(clamp_move_target(from, to, bounds.rows)
+                    .map(|to| editor_state.execute(Operation::MoveRow { sheet_index, from, to })))
             }
             None => Err(AppError::Internal("No file loaded".to_string())),
         }
     };
 
-    // Note: Adding a sheet doesn't require index rebuild since it's a new empty sheet
+    // 行移动使区间内的行位置整体平移，异步重建索引
+    if matches!(result, Ok(Some(_))) {
+        spawn_rebuild_sheet_index(sheet_index, state.clone());
+    }
 
     result
 }
 
+/// 移动列：每行 from 列的单元格移到 to 列（超出末尾时移到最后一列），一次撤销即可恢复
+/// 移动没有效果时（只有一列或目标即原位置）不记录操作，返回 None
+pub fn do_move_column(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    from: usize,
+    to: usize,
+) -> Result<Option<OperationResult>, AppError> {
+    let result = {
+        let mut state_guard = state.write().unwrap();
+        match state_guard.as_mut() {
+            Some(editor_state) => {
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                let bounds = Bounds::of(sheet);
+                bounds.check_col(from)?;
+                let row_lens = sheet.rows.iter().map(|row| row.len()).collect();
+                Ok(clamp_move_target(from, to, bounds.cols).map(|to| {
+                    editor_state.execute(Operation::MoveColumn { sheet_index, from, to, row_lens })
+                }))
+            }
+            None => Err(AppError::Internal("No file loaded".to_string())),
+        }
+    };
+
+    // 列移动使区间内的列位置整体平移，异步重建索引
+    if matches!(result, Ok(Some(_))) {
+        spawn_rebuild_sheet_index(sheet_index, state.clone());
+    }
+
+    result
+}
+
+/// 添加 Sheet（使用默认行列数）
+pub fn do_add_sheet(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResult, AppError> {
+    do_add_sheet_sized(state, None)
+}
+
+/// 添加指定行列数的 Sheet，size 为 None 时使用 EditorState 中的默认大小
+pub fn do_add_sheet_sized(
+    state: Arc<RwLock<Option<EditorState>>>,
+    size: Option<(usize, usize)>,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    match state_guard.as_mut() {
+        Some(editor_state) => {
+            let size = match size {
+                Some(size) => check_sheet_size(size)?,
+                None => editor_state.default_sheet_size,
+            };
+            // 传入空字符串和 None，让 execute 生成名称并创建空 sheet
+            let operation = Operation::AddSheet {
+                name: String::new(),
+                sheet_data: None,
+                sheet_index: None,
+                size: Some(size),
+            };
+            // Note: Adding a sheet doesn't require index rebuild since it's a new empty sheet
+            Ok(editor_state.execute(operation))
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 添加指定名称的 Sheet（名称需非空且不与已有 sheet 重名，大小使用默认值）
+pub fn do_add_sheet_named(state: Arc<RwLock<Option<EditorState>>>, name: String) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    match state_guard.as_mut() {
+        Some(editor_state) => {
+            let name = validate_sheet_name(editor_state.file_data.sheets.iter().map(|s| s.name.as_str()), &name)?;
+            let operation = Operation::AddSheet {
+                name,
+                sheet_data: None,
+                sheet_index: None,
+                size: Some(editor_state.default_sheet_size),
+            };
+            Ok(editor_state.execute(operation))
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 设置新建 Sheet 的默认行列数
+pub fn do_set_default_sheet_size(
+    state: Arc<RwLock<Option<EditorState>>>,
+    rows: usize,
+    cols: usize,
+) -> Result<(), AppError> {
+    let mut state_guard = state.write().unwrap();
+    match state_guard.as_mut() {
+        Some(editor_state) => {
+            editor_state.default_sheet_size = check_sheet_size((rows, cols))?;
+            Ok(())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 设置添加行/列时新单元格的值
+pub fn do_set_default_cell(state: Arc<RwLock<Option<EditorState>>>, value: CellValue) -> Result<(), AppError> {
+    let mut state_guard = state.write().unwrap();
+    match state_guard.as_mut() {
+        Some(editor_state) => {
+            editor_state.default_cell = value;
+            Ok(())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
 /// 删除 Sheet
 pub fn do_delete_sheet(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Result<(), AppError> {
     let result = {
         let mut state_guard = state.write().unwrap();
         match state_guard.as_mut() {
             Some(editor_state) => {
+                check_sheet(&editor_state.file_data, sheet_index)?;
                 // sheet_data 为空，会在 execute 中自动保存
                 let operation = Operation::DeleteSheet {
                     sheet_index,
@@ -186,3 +501,255 @@ pub fn do_delete_sheet(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usi
 
     result
 }
+
+/// 移动 Sheet：from 位置的 sheet 移到 to（超出末尾时移到最后），一次撤销即可恢复
+/// 索引随 sheet 一起移动，无需重建；移动没有效果时不记录操作，返回 None
+pub fn do_move_sheet(
+    state: Arc<RwLock<Option<EditorState>>>,
+    from: usize,
+    to: usize,
+) -> Result<Option<OperationResult>, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    check_sheet(&editor_state.file_data, from)?;
+    let len = editor_state.file_data.sheets.len();
+    Ok(clamp_move_target(from, to, len).map(|to| editor_state.execute(Operation::MoveSheet { from, to })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::editor_ops::{do_redo, do_undo};
+    use crate::ops::test_util::{rows, search, state_with, text};
+
+    fn history_len(state: &Arc<RwLock<Option<EditorState>>>) -> usize {
+        state.read().unwrap().as_ref().unwrap().history.len()
+    }
+
+    fn sheet_names(state: &Arc<RwLock<Option<EditorState>>>) -> Vec<String> {
+        let state = state.read().unwrap();
+        state.as_ref().unwrap().file_data.sheets.iter().map(|s| s.name.clone()).collect()
+    }
+
+    #[test]
+    fn add_column_on_empty_sheet_creates_one_cell() {
+        let state = state_with(vec![vec![]]);
+        do_add_column(state.clone(), 0).unwrap();
+        assert_eq!(rows(&state, 0), [[CellValue::Null]]);
+
+        do_add_column(state.clone(), 0).unwrap();
+        assert_eq!(rows(&state, 0), [[CellValue::Null, CellValue::Null]]);
+
+        do_undo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert!(rows(&state, 0).is_empty());
+    }
+
+    #[test]
+    fn add_row_on_empty_sheet_creates_one_cell() {
+        let state = state_with(vec![vec![]]);
+        do_add_row(state.clone(), 0, 0).unwrap();
+        assert_eq!(rows(&state, 0), [[CellValue::Null]]);
+
+        do_undo(state.clone()).unwrap();
+        assert!(rows(&state, 0).is_empty());
+    }
+
+    #[test]
+    fn add_row_with_values_on_empty_sheet_keeps_their_width() {
+        let state = state_with(vec![vec![]]);
+        do_add_row_with_values(state.clone(), 0, 0, vec![text("a"), text("b"), text("c")]).unwrap();
+        assert_eq!(rows(&state, 0), [[text("a"), text("b"), text("c")]]);
+    }
+
+    #[test]
+    fn empty_sheet_uses_default_cell() {
+        let state = state_with(vec![vec![], vec![]]);
+        do_set_default_cell(state.clone(), text("-")).unwrap();
+        do_add_row(state.clone(), 0, 0).unwrap();
+        do_add_column(state.clone(), 1).unwrap();
+        assert_eq!(rows(&state, 0), [[text("-")]]);
+        assert_eq!(rows(&state, 1), [[text("-")]]);
+    }
+
+    #[test]
+    fn duplicate_column_on_ragged_rows_undoes_exactly() {
+        let original = vec![
+            vec![text("a"), text("b"), text("c")],
+            vec![text("d")],
+            vec![],
+            vec![text("e"), text("f")],
+        ];
+        let state = state_with(vec![original.clone()]);
+
+        do_duplicate_column(state.clone(), 0, 1).unwrap();
+        assert_eq!(
+            rows(&state, 0),
+            vec![
+                vec![text("a"), text("b"), text("b"), text("c")],
+                vec![text("d")],
+                vec![],
+                vec![text("e"), text("f"), text("f")],
+            ]
+        );
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+
+        do_redo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+    }
+
+    #[test]
+    fn delete_column_on_ragged_rows_undoes_exactly() {
+        let original = vec![
+            vec![text("a"), text("b"), text("c")],
+            vec![text("d"), text("e")],
+            vec![text("f")],
+            vec![],
+        ];
+        let state = state_with(vec![original.clone()]);
+
+        do_delete_column(state.clone(), 0, 2).unwrap();
+        assert_eq!(
+            rows(&state, 0),
+            vec![vec![text("a"), text("b")], vec![text("d"), text("e")], vec![text("f")], vec![]]
+        );
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+
+        do_redo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+    }
+
+    #[test]
+    fn add_column_appends_to_each_ragged_row() {
+        let original = vec![vec![text("a")], vec![text("b"), text("c")], vec![]];
+        let state = state_with(vec![original.clone()]);
+
+        do_add_column(state.clone(), 0).unwrap();
+        assert_eq!(
+            rows(&state, 0),
+            vec![
+                vec![text("a"), CellValue::Null],
+                vec![text("b"), text("c"), CellValue::Null],
+                vec![CellValue::Null],
+            ]
+        );
+        assert_eq!(search(&state, 0, "c"), [(1, 1)]);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+
+        do_redo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+    }
+
+    #[test]
+    fn add_column_with_empty_first_row_undoes_the_appended_cells() {
+        let original = vec![vec![], vec![text("a"), text("b")]];
+        let state = state_with(vec![original.clone()]);
+
+        do_add_column(state.clone(), 0).unwrap();
+        assert_eq!(rows(&state, 0), vec![vec![CellValue::Null], vec![text("a"), text("b"), CellValue::Null]]);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+    }
+
+    #[test]
+    fn move_target_past_the_end_is_clamped() {
+        assert_eq!(clamp_move_target(0, 99, 3), Some(2));
+        assert_eq!(clamp_move_target(2, 0, 3), Some(0));
+        assert_eq!(clamp_move_target(2, 99, 3), None);
+        assert_eq!(clamp_move_target(0, 5, 1), None);
+    }
+
+    #[test]
+    fn move_row_carries_row_height_and_undoes() {
+        let state = state_with(vec![vec![vec![text("a")], vec![text("b")], vec![text("c")]]]);
+        state.write().unwrap().as_mut().unwrap().file_data.sheets[0].row_heights = vec![Some(30.0)];
+
+        do_move_row(state.clone(), 0, 0, 99).unwrap().unwrap();
+        assert_eq!(rows(&state, 0), [[text("b")], [text("c")], [text("a")]]);
+        assert_eq!(state.read().unwrap().as_ref().unwrap().file_data.sheets[0].row_height(2), Some(30.0));
+        assert_eq!(search(&state, 0, "a"), [(2, 0)]);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), [[text("a")], [text("b")], [text("c")]]);
+        let heights: Vec<_> = (0..3).map(|r| state.read().unwrap().as_ref().unwrap().file_data.sheets[0].row_height(r)).collect();
+        assert_eq!(heights, [Some(30.0), None, None]);
+        assert_eq!(search(&state, 0, "a"), [(0, 0)]);
+    }
+
+    #[test]
+    fn move_without_effect_is_not_recorded() {
+        let state = state_with(vec![vec![vec![text("a"), text("b")]]]);
+        assert!(do_move_row(state.clone(), 0, 0, 5).unwrap().is_none());
+        assert!(do_move_column(state.clone(), 0, 1, 5).unwrap().is_none());
+        assert!(do_move_sheet(state.clone(), 0, 1).unwrap().is_none());
+        assert_eq!(history_len(&state), 0);
+    }
+
+    #[test]
+    fn move_from_out_of_range_is_rejected() {
+        let state = state_with(vec![vec![vec![text("a"), text("b")]]]);
+        assert!(do_move_row(state.clone(), 0, 1, 0).is_err());
+        assert!(do_move_column(state.clone(), 0, 2, 0).is_err());
+        assert!(do_move_sheet(state.clone(), 1, 0).is_err());
+        assert_eq!(history_len(&state), 0);
+    }
+
+    #[test]
+    fn move_column_on_ragged_rows_undoes_exactly() {
+        let original = vec![
+            vec![text("a"), text("b"), text("c")],
+            vec![text("d")],
+            vec![],
+            vec![text("e"), text("f")],
+        ];
+        let state = state_with(vec![original.clone()]);
+
+        do_move_column(state.clone(), 0, 0, 2).unwrap().unwrap();
+        assert_eq!(
+            rows(&state, 0),
+            vec![
+                vec![text("b"), text("c"), text("a")],
+                vec![CellValue::Null, CellValue::Null, text("d")],
+                vec![],
+                vec![text("f"), CellValue::Null, text("e")],
+            ]
+        );
+        assert_eq!(search(&state, 0, "d"), [(1, 2)]);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+        assert_eq!(search(&state, 0, "d"), [(1, 0)]);
+
+        do_redo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), original);
+    }
+
+    #[test]
+    fn move_sheet_keeps_its_index_and_undoes() {
+        let state = state_with(vec![vec![vec![text("x")]], vec![vec![text("y")]], vec![vec![text("z")]]]);
+        let result = do_move_sheet(state.clone(), 2, 0).unwrap().unwrap();
+        assert!(matches!(result, OperationResult::MoveSheet { from: 2, to: 0, active_sheet_index: 0 }));
+        assert_eq!(sheet_names(&state), ["Sheet3", "Sheet1", "Sheet2"]);
+        assert_eq!(search(&state, 0, "z"), [(0, 0)]);
+        assert!(search(&state, 2, "z").is_empty());
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(sheet_names(&state), ["Sheet1", "Sheet2", "Sheet3"]);
+        assert_eq!(search(&state, 2, "z"), [(0, 0)]);
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::error::AppError;
+use crate::ops::bounds::{check_sheet, Bounds};
+use crate::state::editor_state::EditorState;
+use crate::types::CellValue;
+
+/// 转义 TSV 字段：包含制表符、换行或引号时用双引号包裹，内部引号加倍（与 Excel 粘贴格式一致）
+fn escape_tsv_field(text: &str) -> String {
+    if text.contains(['\t', '\n', '\r', '"']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// 将选区（含首尾，起止顺序不限）导出为 TSV 文本，供前端写入剪贴板
+pub fn do_copy_range_tsv(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    start_row: usize,
+    start_col: usize,
+    end_row: usize,
+    end_col: usize,
+) -> Result<String, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let (top, bottom) = (start_row.min(end_row), start_row.max(end_row));
+    let (left, right) = (start_col.min(end_col), start_col.max(end_col));
+    let bounds = Bounds::of(sheet);
+    bounds.check_row(bottom)?;
+    bounds.check_col(right)?;
+
+    let lines: Vec<String> = sheet.rows[top..=bottom]
+        .iter()
+        .map(|row| {
+            (left..=right)
+                .map(|col| escape_tsv_field(&row.get(col).unwrap_or(&CellValue::Null).to_text()))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
@@ -3,21 +3,37 @@ use std::sync::RwLock;
 
 use crate::ops::index_ops::spawn_rebuild_sheet_index;
 use crate::error::AppError;
-use crate::state::editor_state::EditorState;
+use crate::state::editor_state::{EditorState, Operation, DEFAULT_SHEET_SIZE};
 use crate::state::state::EditorStateInfo;
-use crate::types::OperationResult;
+use crate::types::{CompactFileData, FileData, OperationResult, Tokenizer};
 
-/// 从 OperationResult 中提取 sheet_index
-fn extract_sheet_index(result: &OperationResult) -> usize {
+/// 从 OperationResult 中提取撤销/重做后需要重建索引的 sheet（Group 会展开所有子结果）
+/// - SetCell、AddRow 已在 execute 中增量更新索引
+/// - AddSheet 恢复的 sheet 的索引在 execute 中重置为未构建，首次搜索时构建
+/// - DeleteSheet 的 sheet_index 是被删除的 sheet 的位置，该 sheet 已不存在；其余 sheet 的索引随 sheet 保存，
+///   删除后仍然有效，无需重建
+/// - MoveSheet 只改变 sheet 的顺序，索引随 sheet 一起移动
+/// - 行/列增删、移动和排序会改变整张 sheet 的单元格位置，需要整表重建
+fn sheets_needing_rebuild(result: &OperationResult) -> Vec<usize> {
     match result {
-        OperationResult::SetCell { sheet_index, .. } => *sheet_index,
-        OperationResult::AddRow { sheet_index, .. } => *sheet_index,
-        OperationResult::DeleteRow { sheet_index, .. } => *sheet_index,
-        OperationResult::AddColumn { sheet_index, .. } => *sheet_index,
-        OperationResult::DeleteColumn { sheet_index, .. } => *sheet_index,
-        OperationResult::AddSheet { sheet_index, .. } => *sheet_index,
-        OperationResult::DeleteSheet { sheet_index, .. } => *sheet_index,
-        OperationResult::SortColumn { sheet_index, .. } => *sheet_index,
+        OperationResult::SetCell { .. }
+        | OperationResult::AddRow { .. }
+        | OperationResult::AddSheet { .. }
+        | OperationResult::DeleteSheet { .. }
+        | OperationResult::MoveSheet { .. } => vec![],
+        OperationResult::DeleteRow { sheet_index, .. }
+        | OperationResult::AddColumn { sheet_index, .. }
+        | OperationResult::DeleteColumn { sheet_index, .. }
+        | OperationResult::SortColumn { sheet_index, .. }
+        | OperationResult::ResizeSheet { sheet_index, .. }
+        | OperationResult::MoveRow { sheet_index, .. }
+        | OperationResult::MoveColumn { sheet_index, .. } => vec![*sheet_index],
+        OperationResult::Group { results } => {
+            let mut indices: Vec<usize> = results.iter().flat_map(sheets_needing_rebuild).collect();
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+        }
     }
 }
 
@@ -27,14 +43,77 @@ fn get_editor_state_info(state: &Arc<RwLock<Option<EditorState>>>) -> Option<Edi
     state.as_ref().map(|s| EditorStateInfo {
         can_undo: s.can_undo,
         can_redo: s.can_redo,
+        sync_index: s.sync_index,
+        default_cell: s.default_cell.clone(),
     })
 }
 
+/// 获取当前文件数据的副本（SheetIndex 不参与序列化，不会发送给前端）
+pub fn do_get_file_data(state: Arc<RwLock<Option<EditorState>>>) -> Result<FileData, AppError> {
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => Ok(editor_state.file_data.clone()),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 获取紧凑编码的文件数据 JSON（编码格式见 CompactFileData），直接从编辑器状态序列化，不克隆数据
+pub fn do_get_file_data_compact(state: Arc<RwLock<Option<EditorState>>>) -> Result<String, AppError> {
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => serde_json::to_string(&CompactFileData::from(&editor_state.file_data))
+            .map_err(|e| AppError::Internal(e.to_string())),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 当前文件内容的哈希（十六进制字符串，避免 u64 在前端丢失精度）
+pub fn do_content_hash(state: Arc<RwLock<Option<EditorState>>>) -> Result<String, AppError> {
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => Ok(format!("{:016x}", editor_state.file_data.content_hash())),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 按内容判断是否有未保存的修改：与加载/上次保存时的内容哈希比较，修改后又撤销回原样时返回 false
+pub fn do_is_dirty(state: Arc<RwLock<Option<EditorState>>>) -> Result<bool, AppError> {
+    let state = state.read().unwrap();
+    match state.as_ref() {
+        Some(editor_state) => Ok(editor_state.file_data.content_hash() != editor_state.saved_hash),
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
 /// 获取编辑器状态（包含能否撤销/重做）
 pub fn do_get_editor_state(state: Arc<RwLock<Option<EditorState>>>) -> Result<Option<EditorStateInfo>, AppError> {
     Ok(get_editor_state_info(&state))
 }
 
+/// 设置是否同步重建索引
+pub fn do_set_sync_index(state: Arc<RwLock<Option<EditorState>>>, enabled: bool) -> Result<(), AppError> {
+    let mut state = state.write().unwrap();
+    match state.as_mut() {
+        Some(editor_state) => {
+            editor_state.sync_index = enabled;
+            Ok(())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 设置按词索引的分词方式（已有索引按新方式重建）
+pub fn do_set_tokenizer(state: Arc<RwLock<Option<EditorState>>>, tokenizer: Tokenizer) -> Result<(), AppError> {
+    let mut state = state.write().unwrap();
+    match state.as_mut() {
+        Some(editor_state) => {
+            editor_state.set_tokenizer(tokenizer);
+            Ok(())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
 /// 撤销操作
 pub fn do_undo(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResult, AppError> {
     let sheet_index = {
@@ -42,7 +121,7 @@ pub fn do_undo(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResul
         match state.as_mut() {
             Some(editor_state) => {
                 if let Some(result) = editor_state.undo() {
-                    let idx = extract_sheet_index(&result);
+                    let idx = sheets_needing_rebuild(&result);
                     (result, idx)
                 } else {
                     return Err(AppError::Internal("Nothing to undo".to_string()));
@@ -53,7 +132,9 @@ pub fn do_undo(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResul
     };
 
     // 异步重建索引
-    spawn_rebuild_sheet_index(sheet_index.1, state);
+    for idx in sheet_index.1 {
+        spawn_rebuild_sheet_index(idx, state.clone());
+    }
 
     Ok(sheet_index.0)
 }
@@ -65,7 +146,7 @@ pub fn do_redo(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResul
         match state.as_mut() {
             Some(editor_state) => {
                 if let Some(result) = editor_state.redo() {
-                    let idx = extract_sheet_index(&result);
+                    let idx = sheets_needing_rebuild(&result);
                     (result, idx)
                 } else {
                     return Err(AppError::Internal("Nothing to redo".to_string()));
@@ -76,7 +157,290 @@ pub fn do_redo(state: Arc<RwLock<Option<EditorState>>>) -> Result<OperationResul
     };
 
     // 异步重建索引
-    spawn_rebuild_sheet_index(sheet_index.1, state);
+    for idx in sheet_index.1 {
+        spawn_rebuild_sheet_index(idx, state.clone());
+    }
 
     Ok(sheet_index.0)
 }
+
+/// 将本次会话的操作历史（撤销栈）导出为 JSON 文件，可用 import_operations 在另一份数据上重放
+pub fn do_export_operations(state: Arc<RwLock<Option<EditorState>>>, path: String) -> Result<usize, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let file = std::fs::File::create(&path).map_err(|e| AppError::WriteError(e.to_string()))?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &editor_state.history)
+        .map_err(|e| AppError::WriteError(e.to_string()))?;
+    Ok(editor_state.history.len())
+}
+
+/// 按顺序校验操作在当前数据上能否安全重放（只跟踪每个 sheet 的行数）
+/// 越界的行/sheet 插入或删除在 execute 中会直接 panic，必须在执行前拒绝
+fn check_replay(sheet_rows: &mut Vec<usize>, operation: &Operation) -> Result<(), AppError> {
+    let check_sheet = |sheet_rows: &Vec<usize>, sheet_index: usize| {
+        if sheet_index < sheet_rows.len() {
+            Ok(())
+        } else {
+            Err(AppError::OutOfRange { what: "Sheet", index: sheet_index, len: sheet_rows.len() })
+        }
+    };
+
+    match operation {
+        Operation::SetCell { sheet_index, .. }
+        | Operation::AddColumn { sheet_index, .. }
+        | Operation::DeleteColumn { sheet_index, .. }
+        | Operation::SortColumn { sheet_index, .. }
+        | Operation::MoveRow { sheet_index, .. }
+        | Operation::MoveColumn { sheet_index, .. } => check_sheet(sheet_rows, *sheet_index),
+        Operation::MoveSheet { from, to } => {
+            check_sheet(sheet_rows, *from)?;
+            let rows = sheet_rows.remove(*from);
+            sheet_rows.insert((*to).min(sheet_rows.len()), rows);
+            Ok(())
+        }
+        Operation::AddRow { sheet_index, row_index, .. } => {
+            check_sheet(sheet_rows, *sheet_index)?;
+            let rows = &mut sheet_rows[*sheet_index];
+            if *row_index > *rows {
+                return Err(AppError::OutOfRange { what: "Row", index: *row_index, len: *rows + 1 });
+            }
+            *rows += 1;
+            Ok(())
+        }
+        Operation::ResizeSheet { sheet_index, rows, .. } => {
+            check_sheet(sheet_rows, *sheet_index)?;
+            sheet_rows[*sheet_index] = *rows;
+            Ok(())
+        }
+        Operation::RestoreRows { sheet_index, rows, .. } => {
+            check_sheet(sheet_rows, *sheet_index)?;
+            sheet_rows[*sheet_index] = rows.len();
+            Ok(())
+        }
+        Operation::DeleteRow { sheet_index, row_index, .. } => {
+            check_sheet(sheet_rows, *sheet_index)?;
+            let rows = &mut sheet_rows[*sheet_index];
+            if *row_index < *rows {
+                *rows -= 1;
+            }
+            Ok(())
+        }
+        Operation::AddSheet { sheet_data, sheet_index, size, .. } => {
+            let index = sheet_index.unwrap_or(sheet_rows.len());
+            if index > sheet_rows.len() {
+                return Err(AppError::OutOfRange { what: "Sheet", index, len: sheet_rows.len() + 1 });
+            }
+            let rows = match sheet_data {
+                Some(data) => data.rows.len(),
+                None => size.unwrap_or(DEFAULT_SHEET_SIZE).0,
+            };
+            sheet_rows.insert(index, rows);
+            Ok(())
+        }
+        Operation::DeleteSheet { sheet_index, sheet_data } => {
+            // 与 execute 一致：正常删除时保留最后一个 sheet，usize::MAX 表示删除最后一个
+            if sheet_rows.len() <= 1 && sheet_data.is_empty() {
+                return Ok(());
+            }
+            let index = if *sheet_index == usize::MAX { sheet_rows.len().saturating_sub(1) } else { *sheet_index };
+            check_sheet(sheet_rows, index)?;
+            sheet_rows.remove(index);
+            Ok(())
+        }
+        Operation::Group { operations } => operations.iter().try_for_each(|op| check_replay(sheet_rows, op)),
+    }
+}
+
+/// 从 JSON 文件读取操作列表并依次重放到当前数据上，返回重放的操作数
+/// 每个操作单独记入撤销历史；重放前整体校验，任一操作越界则不做任何修改
+/// 重放后所有 sheet 的索引失效，开启 sync_index 时立即重建，否则在首次搜索时构建
+pub fn do_import_operations(state: Arc<RwLock<Option<EditorState>>>, path: String) -> Result<usize, AppError> {
+    let file = std::fs::File::open(&path).map_err(|e| AppError::ReadError(e.to_string()))?;
+    let operations: Vec<Operation> = serde_json::from_reader(std::io::BufReader::new(file))
+        .map_err(|e| AppError::ReadError(e.to_string()))?;
+
+    let mut state = state.write().unwrap();
+    let editor_state = match state.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let mut sheet_rows: Vec<usize> = editor_state.file_data.sheets.iter().map(|s| s.rows.len()).collect();
+    for operation in &operations {
+        check_replay(&mut sheet_rows, operation)?;
+    }
+
+    let count = operations.len();
+    for operation in operations {
+        editor_state.execute(operation);
+    }
+
+    if editor_state.sync_index {
+        editor_state.rebuild_all_indices();
+    } else {
+        for sheet in &mut editor_state.file_data.sheets {
+            sheet.index = Arc::default();
+        }
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::cell_ops::{
+        do_add_column, do_add_row_with_values, do_add_sheet, do_delete_column, do_delete_row, do_delete_sheet, do_set_cell,
+    };
+    use crate::ops::replace_ops::do_replace_all;
+    use crate::ops::test_util::{search, state_with, text};
+    use crate::types::CellValue;
+
+    /// 3×3 的 sheet，单元格为 "r{行}c{列}"
+    fn grid() -> Vec<Vec<CellValue>> {
+        (0..3).map(|r| (0..3).map(|c| text(&format!("r{r}c{c}"))).collect()).collect()
+    }
+
+    /// 每个 sheet 中每个非空单元格都能按整格搜索命中，且命中位置与当前数据一致
+    fn assert_searchable(state: &Arc<RwLock<Option<EditorState>>>) {
+        let sheets = state.read().unwrap().as_ref().unwrap().file_data.sheets.clone();
+        for (sheet_index, sheet) in sheets.iter().enumerate() {
+            for row in &sheet.rows {
+                for cell in row.iter().filter(|c| !c.to_text().is_empty()) {
+                    let query = cell.to_text();
+                    let mut expected = Vec::new();
+                    for (r, cells) in sheet.rows.iter().enumerate() {
+                        for (c, other) in cells.iter().enumerate() {
+                            if other.to_text().to_lowercase() == query.to_lowercase() {
+                                expected.push((r, c));
+                            }
+                        }
+                    }
+                    assert_eq!(search(state, sheet_index, &query), expected, "sheet {sheet_index}, query {query:?}");
+                }
+            }
+        }
+    }
+
+    /// 两个 sheet 的状态，先搜索一次使索引已构建（撤销时走增量更新或重建路径）
+    fn built_state() -> Arc<RwLock<Option<EditorState>>> {
+        let state = state_with(vec![grid(), grid()]);
+        assert_searchable(&state);
+        state
+    }
+
+    #[test]
+    fn undo_delete_sheet_restores_search() {
+        let state = state_with(vec![vec![vec![text("keep")]], vec![vec![text("needle"), text("other")]]]);
+        assert_eq!(search(&state, 1, "needle"), vec![(0, 0)]);
+
+        do_delete_sheet(state.clone(), 1).unwrap();
+        do_undo(state.clone()).unwrap();
+
+        assert_eq!(search(&state, 1, "needle"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "keep"), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn search_after_undo_set_cell() {
+        let state = built_state();
+        do_set_cell(state.clone(), 0, 1, 1, text("r1c1"), text("changed")).unwrap();
+        assert_eq!(search(&state, 0, "changed"), vec![(1, 1)]);
+
+        do_undo(state.clone()).unwrap();
+        assert!(search(&state, 0, "changed").is_empty());
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_add_row() {
+        let state = built_state();
+        do_add_row_with_values(state.clone(), 0, 1, vec![text("new")]).unwrap();
+        assert_eq!(search(&state, 0, "r2c0"), vec![(3, 0)]);
+
+        do_undo(state.clone()).unwrap();
+        assert!(search(&state, 0, "new").is_empty());
+        assert_eq!(search(&state, 0, "r2c0"), vec![(2, 0)]);
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_delete_row() {
+        let state = built_state();
+        do_delete_row(state.clone(), 0, 0).unwrap();
+        assert!(search(&state, 0, "r0c0").is_empty());
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(search(&state, 0, "r0c0"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "r2c2"), vec![(2, 2)]);
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_add_column() {
+        let state = built_state();
+        do_add_column(state.clone(), 0).unwrap();
+        do_set_cell(state.clone(), 0, 0, 3, CellValue::Null, text("new")).unwrap();
+        assert_eq!(search(&state, 0, "new"), vec![(0, 3)]);
+
+        do_undo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert!(search(&state, 0, "new").is_empty());
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_delete_column() {
+        let state = built_state();
+        do_delete_column(state.clone(), 0, 0).unwrap();
+        assert_eq!(search(&state, 0, "r0c1"), vec![(0, 0)]);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(search(&state, 0, "r0c0"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "r0c1"), vec![(0, 1)]);
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_add_sheet() {
+        let state = built_state();
+        do_add_sheet(state.clone()).unwrap();
+        do_set_cell(state.clone(), 2, 0, 0, CellValue::Null, text("new")).unwrap();
+        assert_eq!(search(&state, 2, "new"), vec![(0, 0)]);
+
+        do_undo(state.clone()).unwrap();
+        do_undo(state.clone()).unwrap();
+        assert_eq!(state.read().unwrap().as_ref().unwrap().file_data.sheets.len(), 2);
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_delete_sheet() {
+        let state = built_state();
+        do_set_cell(state.clone(), 1, 0, 0, text("r0c0"), text("second")).unwrap();
+        do_delete_sheet(state.clone(), 0).unwrap();
+        assert_eq!(search(&state, 0, "second"), vec![(0, 0)]);
+
+        do_undo(state.clone()).unwrap();
+        assert!(search(&state, 0, "second").is_empty());
+        assert_eq!(search(&state, 1, "second"), vec![(0, 0)]);
+        assert_searchable(&state);
+    }
+
+    #[test]
+    fn search_after_undo_group() {
+        let state = built_state();
+        do_set_cell(state.clone(), 0, 2, 2, text("r2c2"), text("r0c0")).unwrap();
+        assert_eq!(do_replace_all(state.clone(), 0, "r0c0".to_string(), text("replaced"), None).unwrap(), 2);
+        assert_eq!(search(&state, 0, "replaced"), vec![(0, 0), (2, 2)]);
+
+        do_undo(state.clone()).unwrap();
+        assert!(search(&state, 0, "replaced").is_empty());
+        assert_eq!(search(&state, 0, "r0c0"), vec![(0, 0), (2, 2)]);
+        assert_searchable(&state);
+    }
+}
@@ -1,65 +1,251 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use crate::error::AppError;
+use crate::ops::bounds::check_sheet;
 use crate::state::editor_state::EditorState;
-use crate::types::{CellPosition, CellValue, SheetData};
+use crate::types::{format_number, CellPosition, CellValue, IndexStats, SheetData, SheetIndex, Tokenizer};
 
-/// 将单元格值转换为字符串
-fn cell_to_string(cell: &CellValue) -> String {
-    match cell {
-        CellValue::Null => String::new(),
-        CellValue::String(s) => s.clone(),
-        CellValue::Number(n) => n.to_string(),
-        CellValue::Boolean(b) => b.to_string(),
+/// 如果查询是数字（如 "1000.00"、"+5"、"-.5"），返回其规范文本形式
+/// 整数先按 i64 解析，长整数不会因转换为 f64 而丢失精度
+pub fn numeric_token(query: &str) -> Option<String> {
+    let query = query.trim();
+    if let Ok(i) = query.parse::<i64>() {
+        return Some(i.to_string());
     }
+    query
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .map(format_number)
 }
 
-/// 重建单个 sheet 的索引
-pub fn rebuild_sheet_index(sheet: &mut SheetData) {
-    let mut inverted_index: HashMap<String, Vec<CellPosition>> = HashMap::new();
-
-    for (row_idx, row) in sheet.rows.iter().enumerate() {
-        for (col_idx, cell) in row.iter().enumerate() {
-            let text = cell_to_string(cell);
-            if !text.is_empty() {
-                let token = text.to_lowercase();
-                inverted_index
-                    .entry(token)
-                    .or_default()
-                    .push(CellPosition {
-                        row: row_idx,
-                        col: col_idx,
-                    });
-            }
+/// 按分词方式将文本拆分为小写单词（去重，保持首次出现顺序）
+pub fn tokenize_words(text: &str, tokenizer: Tokenizer) -> Vec<String> {
+    let pieces: Box<dyn Iterator<Item = &str>> = match tokenizer {
+        Tokenizer::Punctuation => Box::new(text.split(|c: char| !c.is_alphanumeric())),
+        Tokenizer::Whitespace => Box::new(text.split_whitespace()),
+        Tokenizer::WholeCell => Box::new(std::iter::once(text.trim())),
+    };
+    // 用 HashSet 去重：超长单元格可能有上万个不同的词，线性查找会退化为平方复杂度
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut words: Vec<String> = Vec::new();
+    for word in pieces.filter(|w| !w.is_empty()) {
+        let word = word.to_lowercase();
+        if seen.insert(word.clone()) {
+            words.push(word);
         }
     }
+    words
+}
+
+/// 超过此字节数的单元格不建索引（分词和转小写的开销过大），搜索不会命中这些单元格
+const MAX_INDEXED_TEXT_LEN: usize = 64 * 1024;
 
-    sheet.index.inverted_index = inverted_index;
+/// 单元格文本是否需要建索引（空文本和超长文本不建索引）
+fn is_indexable(text: &str) -> bool {
+    !text.is_empty() && text.len() <= MAX_INDEXED_TEXT_LEN
 }
 
-/// 异步重建指定 sheet 的索引
-pub fn spawn_rebuild_sheet_index(sheet_index: usize, state: Arc<RwLock<Option<EditorState>>>) {
-    std::thread::spawn(move || {
-        if let Ok(mut guard) = state.write() {
-            if let Some(ref mut editor_state) = *guard {
-                if let Some(sheet) = editor_state.file_data.sheets.get_mut(sheet_index) {
-                    rebuild_sheet_index(sheet);
-                }
+/// 将单元格加入索引（整格 token 和单词 token）
+pub fn index_cell(index: &mut SheetIndex, text: &str, pos: CellPosition) {
+    if !is_indexable(text) {
+        return;
+    }
+    for word in tokenize_words(text, index.tokenizer) {
+        index.word_index.entry(word).or_default().push(pos.clone());
+    }
+    index.inverted_index.entry(text.to_lowercase()).or_default().push(pos);
+}
+
+/// 将单元格从索引中移除
+pub fn unindex_cell(index: &mut SheetIndex, text: &str, pos: &CellPosition) {
+    if !is_indexable(text) {
+        return;
+    }
+    let remove = |map: &mut HashMap<String, Vec<CellPosition>>, token: String| {
+        if let Some(positions) = map.get_mut(&token) {
+            positions.retain(|p| !(p.row == pos.row && p.col == pos.col));
+            if positions.is_empty() {
+                map.remove(&token);
             }
         }
-    });
+    };
+    for word in tokenize_words(text, index.tokenizer) {
+        remove(&mut index.word_index, word);
+    }
+    remove(&mut index.inverted_index, text.to_lowercase());
 }
 
-/// 异步重建所有 sheets 的索引
-pub fn spawn_rebuild_all_sheets_index(state: Arc<RwLock<Option<EditorState>>>) {
-    std::thread::spawn(move || {
-        if let Ok(mut guard) = state.write() {
-            if let Some(ref mut editor_state) = *guard {
-                for sheet in &mut editor_state.file_data.sheets {
-                    rebuild_sheet_index(sheet);
-                }
-            }
+/// 根据行数据构建完整索引
+fn build_index(rows: &[Vec<CellValue>], tokenizer: Tokenizer) -> SheetIndex {
+    let mut index = SheetIndex {
+        built: true,
+        tokenizer,
+        ..SheetIndex::default()
+    };
+
+    for (row_idx, row) in rows.iter().enumerate() {
+        for (col_idx, cell) in row.iter().enumerate() {
+            index_cell(&mut index, &cell.to_text(), CellPosition { row: row_idx, col: col_idx });
+        }
+    }
+
+    index
+}
+
+/// 重建单个 sheet 的索引
+pub fn rebuild_sheet_index(sheet: &mut SheetData, tokenizer: Tokenizer) {
+    sheet.index = Arc::new(build_index(&sheet.rows, tokenizer));
+}
+
+/// 在读锁下构建 sheet 的索引，再短暂获取写锁替换，构建期间搜索不会被阻塞
+/// 如果构建期间数据被修改（revision 变化），则在写锁下重新构建，保证索引与数据一致
+/// build_unbuilt 为 false 时，尚未构建的索引保持未构建
+fn refresh_sheet_index(state: &Arc<RwLock<Option<EditorState>>>, sheet_index: usize, build_unbuilt: bool) {
+    let (index, revision) = {
+        let Ok(guard) = state.read() else { return };
+        let Some(editor_state) = guard.as_ref() else { return };
+        let Some(sheet) = editor_state.file_data.sheets.get(sheet_index) else { return };
+        if !sheet.index.built && !build_unbuilt {
+            return;
         }
-    });
+        (build_index(&sheet.rows, editor_state.tokenizer), editor_state.revision)
+    };
+
+    let Ok(mut guard) = state.write() else { return };
+    let Some(editor_state) = guard.as_mut() else { return };
+    let unchanged = editor_state.revision == revision;
+    let tokenizer = editor_state.tokenizer;
+    let Some(sheet) = editor_state.file_data.sheets.get_mut(sheet_index) else { return };
+    if unchanged {
+        sheet.index = Arc::new(index);
+    } else if sheet.index.built || build_unbuilt {
+        rebuild_sheet_index(sheet, tokenizer);
+    }
+}
+
+/// 统计指定 sheet 整格索引的 token 数、位置数和估算内存
+pub fn do_index_stats(state: Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Result<IndexStats, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let inverted_index = &sheet.index.inverted_index;
+    let total_postings: usize = inverted_index.values().map(Vec::len).sum();
+    let key_bytes: usize = inverted_index.keys().map(String::len).sum();
+
+    Ok(IndexStats {
+        token_count: inverted_index.len(),
+        total_postings,
+        bytes_estimate: key_bytes + total_postings * std::mem::size_of::<CellPosition>(),
+        built: sheet.index.built,
+    })
+}
+
+/// 确保要搜索的 sheet 已构建索引（sheet_index 为 None 时为所有 sheet）
+/// 索引在首次搜索时才构建；只有存在未构建的索引时才获取写锁
+pub fn ensure_indices_built(state: &Arc<RwLock<Option<EditorState>>>, sheet_index: Option<usize>) {
+    let is_target = |i: usize| sheet_index.is_none_or(|s| s == i);
+    let unbuilt: Vec<usize> = match state.read() {
+        Ok(guard) => guard.as_ref().map_or_else(Vec::new, |editor_state| {
+            let sheets = editor_state.file_data.sheets.iter().enumerate();
+            sheets.filter(|(i, sheet)| is_target(*i) && !sheet.index.built).map(|(i, _)| i).collect()
+        }),
+        Err(_) => return,
+    };
+    for sheet_index in unbuilt {
+        refresh_sheet_index(state, sheet_index, true);
+    }
+}
+
+/// 是否开启了同步索引（调用方必须已释放 state 的锁）
+fn is_sync_index(state: &Arc<RwLock<Option<EditorState>>>) -> bool {
+    state
+        .read()
+        .map(|guard| guard.as_ref().is_some_and(|s| s.sync_index))
+        .unwrap_or(false)
+}
+
+/// 重建指定 sheet 的索引（索引尚未构建时不处理，留待首次搜索时构建）
+/// 默认在后台线程执行；开启 sync_index 时在当前线程执行，返回后索引即为最新
+pub fn spawn_rebuild_sheet_index(sheet_index: usize, state: Arc<RwLock<Option<EditorState>>>) {
+    let sync = is_sync_index(&state);
+    let rebuild = move || refresh_sheet_index(&state, sheet_index, false);
+
+    if sync {
+        rebuild();
+    } else {
+        std::thread::spawn(rebuild);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test_util::{search, state_with, text};
+
+    #[test]
+    fn numeric_token_is_canonical() {
+        assert_eq!(numeric_token("1000").as_deref(), Some("1000"));
+        assert_eq!(numeric_token(" +1000 ").as_deref(), Some("1000"));
+        assert_eq!(numeric_token("1000.0").as_deref(), Some("1000"));
+        assert_eq!(numeric_token("1000.50").as_deref(), Some("1000.5"));
+        assert_eq!(numeric_token("-42").as_deref(), Some("-42"));
+        assert_eq!(numeric_token("-0.5").as_deref(), Some("-0.5"));
+        assert_eq!(numeric_token("-0").as_deref(), Some("0"));
+        assert_eq!(numeric_token("1234567890123456789").as_deref(), Some("1234567890123456789"));
+        assert_eq!(numeric_token("abc"), None);
+        assert_eq!(numeric_token("NaN"), None);
+    }
+
+    #[test]
+    fn tokenize_words_dedups_in_first_seen_order() {
+        assert_eq!(tokenize_words("b A, a; c B", Tokenizer::Punctuation), ["b", "a", "c"]);
+
+        let many: Vec<String> = (0..10_000).map(|i| format!("w{i}")).collect();
+        let text = format!("{} {}", many.join(" "), many.join(" "));
+        assert_eq!(tokenize_words(&text, Tokenizer::Whitespace), many);
+    }
+
+    #[test]
+    fn numbers_are_indexed_in_canonical_form() {
+        let rows = vec![vec![CellValue::Number(1000.0), CellValue::Integer(-42), CellValue::Number(-2.5)]];
+        let index = build_index(&rows, Tokenizer::default());
+        assert_eq!(index.inverted_index.get("1000"), Some(&vec![CellPosition { row: 0, col: 0 }]));
+        assert_eq!(index.inverted_index.get("-42"), Some(&vec![CellPosition { row: 0, col: 1 }]));
+        assert_eq!(index.inverted_index.get("-2.5"), Some(&vec![CellPosition { row: 0, col: 2 }]));
+        assert!(!index.inverted_index.contains_key("1000.0"));
+    }
+
+    #[test]
+    fn search_finds_integers() {
+        let state = state_with(vec![vec![vec![CellValue::Integer(1000), CellValue::Number(1000.0), text("1000")]]]);
+        assert_eq!(search(&state, 0, "1000"), vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(search(&state, 0, "+1000"), vec![(0, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn search_finds_decimals() {
+        let state = state_with(vec![vec![vec![CellValue::Number(1000.0), CellValue::Number(0.25), text("1000.0")]]]);
+        // 数字查询按规范形式命中数字单元格，文本 "1000.0" 只按原文命中
+        assert_eq!(search(&state, 0, "1000.0"), vec![(0, 0), (0, 2)]);
+        assert_eq!(search(&state, 0, "1000.00"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "0.250"), vec![(0, 1)]);
+        assert_eq!(search(&state, 0, ".25"), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn search_finds_negatives() {
+        let state = state_with(vec![vec![vec![CellValue::Integer(-42), CellValue::Number(-2.5), CellValue::Number(-0.0)]]]);
+        assert_eq!(search(&state, 0, "-42"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "-42.0"), vec![(0, 0)]);
+        assert_eq!(search(&state, 0, "-2.50"), vec![(0, 1)]);
+        assert_eq!(search(&state, 0, "0"), vec![(0, 2)]);
+        assert!(search(&state, 0, "42").is_empty());
+    }
 }
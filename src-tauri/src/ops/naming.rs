@@ -0,0 +1,99 @@
+use crate::error::AppError;
+
+/// xlsx 不允许出现在 sheet 名称中的字符
+const INVALID_SHEET_NAME_CHARS: &[char] = &['[', ']', ':', '*', '?', '/', '\\'];
+/// xlsx sheet 名称的最大长度
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+/// 清理 sheet 名称：移除非法字符并截断到 31 个字符，结果为空时使用 "Sheet"
+pub fn sanitize_sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !INVALID_SHEET_NAME_CHARS.contains(c))
+        .take(MAX_SHEET_NAME_LEN)
+        .collect();
+    let cleaned = cleaned.trim();
+
+    if cleaned.is_empty() {
+        "Sheet".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// 生成不与已有 sheet 重名的名称（不区分大小写，与 Excel 一致）
+/// 重名时追加数字后缀，如 "Sales (2)"、"Sales (3)"；必要时截断 base，使结果不超过 31 个字符
+pub fn unique_sheet_name<'a, I>(existing: I, base: &str) -> String
+where
+    I: IntoIterator<Item = &'a str> + Clone,
+{
+    let taken = |name: &str| existing.clone().into_iter().any(|s| s.eq_ignore_ascii_case(name));
+
+    if !taken(base) {
+        return base.to_string();
+    }
+
+    (2..)
+        .map(|n| {
+            let suffix = format!(" ({})", n);
+            let keep = MAX_SHEET_NAME_LEN.saturating_sub(suffix.chars().count());
+            let base: String = base.chars().take(keep).collect();
+            format!("{}{}", base.trim_end(), suffix)
+        })
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+/// 校验用户输入的 sheet 名称：去除首尾空白后不能为空、不能含非法字符、不能超长，
+/// 且不能与已有 sheet 重名（不区分大小写）。返回去除空白后的名称
+pub fn validate_sheet_name<'a, I>(existing: I, name: &str) -> Result<String, AppError>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(AppError::InvalidSheetName("name must not be empty".to_string()));
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_SHEET_NAME_CHARS.contains(c)) {
+        return Err(AppError::InvalidSheetName(format!("'{}' contains invalid character '{}'", name, c)));
+    }
+    if name.chars().count() > MAX_SHEET_NAME_LEN {
+        return Err(AppError::InvalidSheetName(format!(
+            "'{}' is longer than {} characters",
+            name, MAX_SHEET_NAME_LEN
+        )));
+    }
+    if existing.into_iter().any(|s| s.eq_ignore_ascii_case(name)) {
+        return Err(AppError::InvalidSheetName(format!("'{}' already exists", name)));
+    }
+
+    Ok(name.to_string())
+}
+
+/// 按名称查找 sheet 索引（不区分大小写，忽略首尾空白）
+/// 多个 sheet 仅大小写不同时，优先取大小写完全一致的那个，否则视为有歧义
+pub fn resolve_sheet_name<'a, I>(names: I, name: &str) -> Result<usize, AppError>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name = name.trim();
+    let matches: Vec<(usize, &str)> = names
+        .into_iter()
+        .enumerate()
+        .filter(|(_, s)| s.eq_ignore_ascii_case(name))
+        .collect();
+
+    match matches.as_slice() {
+        [] => Err(AppError::InvalidSheetName(format!("no sheet named '{}'", name))),
+        [(index, _)] => Ok(*index),
+        _ => match matches.iter().find(|(_, s)| *s == name) {
+            Some((index, _)) => Ok(*index),
+            None => Err(AppError::InvalidSheetName(format!(
+                "'{}' matches multiple sheets: {}",
+                name,
+                matches.iter().map(|(_, s)| *s).collect::<Vec<_>>().join(", ")
+            ))),
+        },
+    }
+}
@@ -1,44 +1,74 @@
 use serde::{Deserialize, Serialize};
+use crate::ops::index_ops::{index_cell, unindex_cell};
 use crate::types::{CellPosition, CellValue, ColumnChange, OperationResult, RowChange, SheetData, SortState};
 
-/// 将单元格值转换为字符串
-fn cell_to_string(cell: &CellValue) -> String {
-    match cell {
-        CellValue::Null => String::new(),
-        CellValue::String(s) => s.clone(),
-        CellValue::Number(n) => n.to_string(),
-        CellValue::Boolean(b) => b.to_string(),
+/// 更新单个单元格的索引（索引未构建时不处理）
+fn update_cell_index(sheet: &mut SheetData, row: usize, col: usize, old_value: &CellValue, new_value: &CellValue) {
+    if !sheet.index.built {
+        return;
     }
-}
 
-/// 更新单个单元格的索引
-fn update_cell_index(sheet: &mut SheetData, row: usize, col: usize, old_value: &CellValue, new_value: &CellValue) {
-    let old_text = cell_to_string(old_value);
-    let new_text = cell_to_string(new_value);
+    let old_text = old_value.to_text();
+    let new_text = new_value.to_text();
+
+    // 索引只按文本建立，文本不变时 token 也不变（如数字 5 改为字符串 "5"），无需更新；
+    // 类型的区别在搜索时按单元格当前值校验（见 search_ops::matching_cells）
+    if old_text == new_text {
+        return;
+    }
 
-    // 如果值没变，不需要更新
-    if old_text.to_lowercase() == new_text.to_lowercase() {
+    // 搜索持有的快照不受影响（写时复制）
+    let index = std::sync::Arc::make_mut(&mut sheet.index);
+    let pos = CellPosition { row, col };
+    unindex_cell(index, &old_text, &pos);
+    index_cell(index, &new_text, pos);
+}
+
+/// 插入行后增量更新索引：下方行的位置下移一行，再加入新行的单元格（索引未构建时不处理）
+fn insert_row_index(sheet: &mut SheetData, row_index: usize) {
+    if !sheet.index.built {
         return;
     }
 
-    // 删除旧值的索引
-    if !old_text.is_empty() {
-        let old_token = old_text.to_lowercase();
-        if let Some(positions) = sheet.index.inverted_index.get_mut(&old_token) {
-            positions.retain(|p| !(p.row == row && p.col == col));
-            if positions.is_empty() {
-                sheet.index.inverted_index.remove(&old_token);
+    let index = std::sync::Arc::make_mut(&mut sheet.index);
+    for positions in index.inverted_index.values_mut().chain(index.word_index.values_mut()) {
+        for pos in positions.iter_mut() {
+            if pos.row >= row_index {
+                pos.row += 1;
             }
         }
     }
 
-    // 添加新值的索引
-    if !new_text.is_empty() {
-        let new_token = new_text.to_lowercase();
-        sheet.index.inverted_index
-            .entry(new_token)
-            .or_default()
-            .push(CellPosition { row, col });
+    let new_row = sheet.rows[row_index].clone();
+    for (col, cell) in new_row.iter().enumerate() {
+        update_cell_index(sheet, row_index, col, &CellValue::Null, cell);
+    }
+}
+
+/// AddColumn 是否在该行的 col 位置插入：有记录的行长度（该列存在时）时按记录判断，
+/// 否则只插入到长度足以到达 col 的行（不规则行中更短的行缺少该列，视为 Null）
+fn has_column(row: &[CellValue], col: usize, len: Option<usize>) -> bool {
+    col <= row.len() && len.is_none_or(|len| col < len)
+}
+
+/// 将 from 位置的元素移到 to（先移除再插入，to 为移动后的位置，超出末尾时移到最后）
+fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) {
+    if from < items.len() {
+        let item = items.remove(from);
+        items.insert(to.min(items.len()), item);
+    }
+}
+
+/// 移动一行中的列：先用 Null 补齐到能容纳 from 和 to，移动后去掉超出 len 的尾部 Null
+/// 不规则行中缺失的单元格视为 Null，移动和撤销都不会改变行的原有长度以外的内容
+fn move_row_cell(row: &mut Vec<CellValue>, from: usize, to: usize, len: usize) {
+    let width = from.max(to) + 1;
+    if row.len() < width {
+        row.resize(width, CellValue::Null);
+    }
+    move_item(row, from, to);
+    while row.len() > len && matches!(row.last(), Some(CellValue::Null)) {
+        row.pop();
     }
 }
 
@@ -54,11 +84,13 @@ fn sort_sheet(sheet: &mut SheetData, col_index: usize, ascending: bool) {
         .map(|(i, row)| (i, row.get(col_index).unwrap_or(&CellValue::Null)))
         .collect();
 
-    // 创建索引数组
+    // 创建索引数组（表头固定在第一行，不参与排序）
+    let start = sheet.data_start();
     let mut indices: Vec<usize> = (0..sheet.rows.len()).collect();
+    let (_, data_indices) = indices.split_at_mut(start);
 
     // 排序
-    indices.sort_by(|&a, &b| {
+    data_indices.sort_by(|&a, &b| {
         let val_a = col_values[a].1;
         let val_b = col_values[b].1;
         let cmp = compare_cell_values(val_a, val_b);
@@ -76,17 +108,24 @@ fn sort_sheet(sheet: &mut SheetData, col_index: usize, ascending: bool) {
 /// 比较两个单元格值（用于排序）
 fn compare_cell_values(a: &CellValue, b: &CellValue) -> std::cmp::Ordering {
     use std::cmp::Ordering;
-    match (a, b) {
+    // 公式单元格按缓存结果排序
+    match (a.cached_value(), b.cached_value()) {
         // Null 排在最后
         (CellValue::Null, CellValue::Null) => Ordering::Equal,
         (CellValue::Null, _) => Ordering::Greater,
         (_, CellValue::Null) => Ordering::Less,
-        // 数字
-        (CellValue::Number(na), CellValue::Number(nb)) => {
-            na.partial_cmp(nb).unwrap_or(Ordering::Equal)
-        }
-        (CellValue::Number(_), _) => Ordering::Greater,
-        (_, CellValue::Number(_)) => Ordering::Less,
+        // 数字：整数之间直接比较，避免大整数转换为 f64 后精度丢失
+        (CellValue::Integer(ia), CellValue::Integer(ib)) => ia.cmp(ib),
+        (
+            na @ (CellValue::Integer(_) | CellValue::Number(_)),
+            nb @ (CellValue::Integer(_) | CellValue::Number(_)),
+        ) => na.as_f64().partial_cmp(&nb.as_f64()).unwrap_or(Ordering::Equal),
+        (CellValue::Integer(_) | CellValue::Number(_), _) => Ordering::Greater,
+        (_, CellValue::Integer(_) | CellValue::Number(_)) => Ordering::Less,
+        // 日期：YYYY-MM-DD 的字典序即时间顺序
+        (CellValue::Date { date: da }, CellValue::Date { date: db }) => da.cmp(db),
+        (CellValue::Date { .. }, _) => Ordering::Greater,
+        (_, CellValue::Date { .. }) => Ordering::Less,
         // 布尔值：true < false
         (CellValue::Boolean(ba), CellValue::Boolean(bb)) => {
             ba.cmp(bb)
@@ -98,9 +137,14 @@ fn compare_cell_values(a: &CellValue, b: &CellValue) -> std::cmp::Ordering {
             // 忽略大小写排序
             sa.to_lowercase().cmp(&sb.to_lowercase())
         }
+        (CellValue::Formula { .. } | CellValue::Formatted { .. }, _)
+        | (_, CellValue::Formula { .. } | CellValue::Formatted { .. }) => Ordering::Equal,
     }
 }
 
+/// 新建空 sheet 的默认行列数（未设置偏好时使用）
+pub const DEFAULT_SHEET_SIZE: (usize, usize) = (5, 5);
+
 /// 操作类型 - 用于撤销/重做
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Operation {
@@ -118,26 +162,40 @@ pub enum Operation {
         row_index: usize,
         /// 被恢复的行数据（用于撤销 DeleteRow）
         row_data: Vec<CellValue>,
+        /// 被恢复的行高（用于撤销 DeleteRow）
+        #[serde(default)]
+        row_height: Option<f64>,
     },
     /// 删除行
     DeleteRow {
         sheet_index: usize,
         row_index: usize,
         row_data: Vec<CellValue>,
+        /// 被删除行的行高（用于撤销时恢复）
+        #[serde(default)]
+        row_height: Option<f64>,
     },
     /// 添加列
     AddColumn {
         sheet_index: usize,
-        /// 记录添加的列索引，用于撤销
+        /// 插入位置，None 表示追加到每一行的末尾（不规则行各自追加）
         col_index: Option<usize>,
         /// 添加的列数据（用于撤销时恢复）
         col_data: Vec<CellValue>,
+        /// 该列存在时各行的长度：只在 col_index 小于记录长度的行中插入（撤销 DeleteColumn 时
+        /// 恢复原本就没有该列的短行保持不变）。为空时插入到长度不小于 col_index 的行
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        row_lens: Vec<usize>,
     },
     /// 删除列
     DeleteColumn {
         sheet_index: usize,
-        col_index: usize,
+        /// 删除位置，None 表示删除每一行的最后一个单元格（撤销追加列）
+        col_index: Option<usize>,
         col_data: Vec<CellValue>,
+        /// 删除前各行的长度（为空时在 execute 中自动保存），撤销时据此只恢复实际删除了单元格的行
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        row_lens: Vec<usize>,
     },
     /// 添加 Sheet（带数据，用于撤销时恢复）
     AddSheet {
@@ -147,6 +205,9 @@ pub enum Operation {
         sheet_data: Option<SheetData>,
         /// 恢复时的原始索引（用于撤销 DeleteSheet 时恢复到正确位置）
         sheet_index: Option<usize>,
+        /// 新建空 sheet 的 (行数, 列数)，None 时使用 DEFAULT_SHEET_SIZE
+        #[serde(default)]
+        size: Option<(usize, usize)>,
     },
     /// 删除 Sheet（带完整数据，用于撤销时恢复）
     DeleteSheet {
@@ -163,6 +224,49 @@ pub enum Operation {
         /// 排序前的 sort_state（用于 undo 时恢复箭头状态）
         previous_sort_state: Option<SortState>,
     },
+    /// 调整 sheet 大小：每行截断或用 Null 补齐到 cols 列，行数截断或补齐到 rows 行
+    ResizeSheet {
+        sheet_index: usize,
+        rows: usize,
+        cols: usize,
+        /// 调整前的行数据（用于 undo 恢复，包括被截断的部分和不规则行的原长度）
+        old_rows: Vec<Vec<CellValue>>,
+        /// 调整前的行高（被截断的行的行高随行一起删除，undo 时恢复）
+        #[serde(default)]
+        old_row_heights: Vec<Option<f64>>,
+    },
+    /// 用保存的行数据和行高替换整个 sheet 的行（ResizeSheet 的撤销操作）
+    RestoreRows {
+        sheet_index: usize,
+        rows: Vec<Vec<CellValue>>,
+        #[serde(default)]
+        row_heights: Vec<Option<f64>>,
+    },
+    /// 移动行：from 行移到 to（to 为移动后的位置），行高随行移动；撤销为反向移动
+    MoveRow {
+        sheet_index: usize,
+        from: usize,
+        to: usize,
+    },
+    /// 移动列：每行 from 列的单元格移到 to 列；撤销为反向移动
+    MoveColumn {
+        sheet_index: usize,
+        from: usize,
+        to: usize,
+        /// 移动前各行的长度：不规则行移动时临时用 Null 补齐，移动后去掉超出原长度的尾部 Null，
+        /// 撤销后各行恢复原长度
+        #[serde(default)]
+        row_lens: Vec<usize>,
+    },
+    /// 移动 sheet：from 位置的 sheet 移到 to；撤销为反向移动
+    MoveSheet {
+        from: usize,
+        to: usize,
+    },
+    /// 操作组：多个操作作为一个撤销步骤（按顺序执行，撤销时逆序撤销）
+    Group {
+        operations: Vec<Operation>,
+    },
 }
 
 /// Trait for operations that can be undone/redone
@@ -202,6 +306,13 @@ impl Undoable for Operation {
                     self.clone()
                 }
             }
+            // Group: let each sub-operation decide its own redo behavior
+            Operation::Group { operations } => Operation::Group {
+                operations: operations
+                    .iter()
+                    .map(|op| op.get_redo_operation(file_data))
+                    .collect(),
+            },
             // Default: return self unchanged
             _ => self.clone()
         }
@@ -239,25 +350,25 @@ impl Operation {
                         col: *col,
                         value: new_value.clone(),
                     },
+                    recorded: true,
                 }
             }
-            Operation::AddRow { sheet_index, row_index, row_data } => {
+            Operation::AddRow { sheet_index, row_index, row_data, row_height } => {
+                let values = row_data.clone();
                 if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
-                    // 使用传入的 row_data，如果为空则创建空行
-                    let new_row = if row_data.is_empty() {
-                        let col_count = sheet.rows.first().map(|r| r.len()).unwrap_or(0);
-                        vec![CellValue::Null; col_count]
-                    } else {
-                        row_data.clone()
-                    };
-                    sheet.rows.insert(*row_index, new_row);
-                    // 索引重建由调用方异步处理
+                    // 新建空行已在 EditorState::execute 中按列数补齐；这里原样插入，
+                    // 撤销删除不规则行中的空行（[]）时恢复为空行
+                    sheet.rows.insert(*row_index, row_data.clone());
+                    sheet.insert_row_height(*row_index, *row_height);
+                    // 增量更新索引（只涉及位置平移和新行）
+                    insert_row_index(sheet, *row_index);
                 }
                 OperationResult::AddRow {
                     sheet_index: *sheet_index,
                     row: RowChange {
                         index: *row_index,
-                        values: row_data.clone(),
+                        values,
+                        height: *row_height,
                     },
                 }
             }
@@ -265,6 +376,7 @@ impl Operation {
                 if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
                     if *row_index < sheet.rows.len() {
                         sheet.rows.remove(*row_index);
+                        sheet.remove_row_height(*row_index);
                     }
                     // 索引重建由调用方异步处理
                 }
@@ -273,20 +385,16 @@ impl Operation {
                     row_index: *row_index,
                 }
             }
-            Operation::AddColumn { sheet_index, col_index, col_data } => {
+            Operation::AddColumn { sheet_index, col_index, col_data, row_lens } => {
                 if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
-                    // 使用传入的 col_data，如果为空则创建空列
-                    let new_col_data = if col_data.is_empty() {
-                        vec![CellValue::Null; sheet.rows.len()]
-                    } else {
-                        col_data.clone()
-                    };
-                    // 添加列数据到每一行
+                    // 添加列数据到每一行（col_data 为空时为空列）：有 col_index 时插入到该位置，
+                    // 否则追加到每一行的末尾
                     for (i, row) in sheet.rows.iter_mut().enumerate() {
-                        if i < new_col_data.len() {
-                            row.push(new_col_data[i].clone());
-                        } else {
-                            row.push(CellValue::Null);
+                        let value = col_data.get(i).cloned().unwrap_or(CellValue::Null);
+                        match col_index {
+                            Some(col) if has_column(row, *col, row_lens.get(i).copied()) => row.insert(*col, value),
+                            Some(_) => {}
+                            None => row.push(value),
                         }
                     }
                     // 索引重建由调用方异步处理
@@ -303,26 +411,45 @@ impl Operation {
                     sheet_index: *sheet_index,
                     column: ColumnChange { index: actual_col_index },
                     col_data: col_data.clone(),
+                    row_lens: row_lens.clone(),
+                    at_end: col_index.is_none(),
                 }
             }
             Operation::DeleteColumn { sheet_index, col_index, .. } => {
+                let mut removed = Vec::new();
                 if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
+                    removed.reserve(sheet.rows.len());
                     for row in &mut sheet.rows {
-                        if *col_index < row.len() {
-                            row.remove(*col_index);
+                        let col = col_index.unwrap_or(row.len().wrapping_sub(1));
+                        if col < row.len() {
+                            removed.push(row.remove(col));
+                        } else {
+                            removed.push(CellValue::Null);
                         }
                     }
                     // 索引重建由调用方异步处理
                 }
+                // 删除末尾单元格时按第一行报告被删除的位置
+                let column_index = col_index.unwrap_or_else(|| {
+                    file_data.sheets
+                        .get(*sheet_index)
+                        .and_then(|s| s.rows.first())
+                        .map_or(0, |r| r.len())
+                });
                 OperationResult::DeleteColumn {
                     sheet_index: *sheet_index,
-                    column_index: *col_index,
+                    column_index,
+                    col_data: removed,
+                    at_end: col_index.is_none(),
                 }
             }
-            Operation::AddSheet { name, sheet_data, sheet_index } => {
+            Operation::AddSheet { name, sheet_data, sheet_index, size } => {
                 // 如果有完整的 sheet_data，直接插入；否则创建空 sheet
                 let (new_sheet, sheet_name) = if let Some(data) = sheet_data {
-                    (data.clone(), data.name.clone())
+                    // 保存的 sheet_data 的索引可能已过时，重置为未构建，首次搜索时再构建
+                    let mut restored = data.clone();
+                    restored.index = std::sync::Arc::default();
+                    (restored, data.name.clone())
                 } else {
                     // 生成新 sheet 名称
                     let final_name = if name.is_empty() {
@@ -333,17 +460,16 @@ impl Operation {
                     };
 
                     // 创建新的空 sheet
+                    let (rows, cols) = size.unwrap_or(DEFAULT_SHEET_SIZE);
                     let new_sheet = SheetData {
                         name: final_name.clone(),
-                        rows: vec![
-                            vec![CellValue::Null; 5],
-                            vec![CellValue::Null; 5],
-                            vec![CellValue::Null; 5],
-                            vec![CellValue::Null; 5],
-                            vec![CellValue::Null; 5],
-                        ],
+                        rows: vec![vec![CellValue::Null; cols]; rows],
                         merges: vec![],
-                        index: crate::types::SheetIndex::default(),
+                        visible: true,
+                        has_header: false,
+                        row_heights: Vec::new(),
+                        freeze: None,
+                        index: std::sync::Arc::default(),
                     };
                     (new_sheet, final_name)
                 };
@@ -354,6 +480,7 @@ impl Operation {
 
                 OperationResult::AddSheet {
                     sheet_index: actual_index,
+                    active_sheet_index: actual_index,
                     name: sheet_name,
                     sheet_data: new_sheet,
                 }
@@ -363,6 +490,7 @@ impl Operation {
                 if file_data.sheets.len() <= 1 && sheet_data.is_empty() {
                     return OperationResult::AddSheet {
                         sheet_index: 0,
+                        active_sheet_index: 0,
                         name: "Error".to_string(),
                         sheet_data: SheetData::default(),
                     };
@@ -377,7 +505,7 @@ impl Operation {
 
                 let removed_sheet = file_data.sheets.remove(actual_index);
 
-                // Adjust current sheet index if needed
+                // 删除后建议的当前 sheet：保持原位置，超出范围时取最后一个
                 let new_current_index = if actual_index >= file_data.sheets.len() {
                     file_data.sheets.len().saturating_sub(1)
                 } else {
@@ -385,7 +513,8 @@ impl Operation {
                 };
 
                 OperationResult::DeleteSheet {
-                    sheet_index: new_current_index,
+                    sheet_index: actual_index,
+                    active_sheet_index: new_current_index,
                     sheet_data: removed_sheet,
                 }
             }
@@ -430,6 +559,82 @@ impl Operation {
                     }
                 }
             }
+            Operation::ResizeSheet { sheet_index, rows, cols, .. } => {
+                let mut new_rows = Vec::new();
+                let mut row_heights = Vec::new();
+                if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
+                    sheet.rows.resize_with(*rows, Vec::new);
+                    for row in &mut sheet.rows {
+                        row.resize(*cols, CellValue::Null);
+                    }
+                    // 被截断的行的行高一起删除，新增的行为默认行高
+                    sheet.row_heights.truncate(*rows);
+                    // 索引重建由调用方异步处理
+                    new_rows = sheet.rows.clone();
+                    row_heights = sheet.row_heights.clone();
+                }
+                OperationResult::ResizeSheet {
+                    sheet_index: *sheet_index,
+                    rows: new_rows,
+                    row_heights,
+                }
+            }
+            Operation::RestoreRows { sheet_index, rows, row_heights } => {
+                if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
+                    sheet.rows = rows.clone();
+                    sheet.row_heights = row_heights.clone();
+                    // 索引重建由调用方异步处理
+                }
+                OperationResult::ResizeSheet {
+                    sheet_index: *sheet_index,
+                    rows: rows.clone(),
+                    row_heights: row_heights.clone(),
+                }
+            }
+            Operation::MoveRow { sheet_index, from, to } => {
+                if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
+                    if *from < sheet.rows.len() {
+                        let height = sheet.row_height(*from);
+                        move_item(&mut sheet.rows, *from, *to);
+                        sheet.remove_row_height(*from);
+                        sheet.insert_row_height((*to).min(sheet.rows.len() - 1), height);
+                    }
+                    // 索引重建由调用方异步处理
+                }
+                OperationResult::MoveRow {
+                    sheet_index: *sheet_index,
+                    from: *from,
+                    to: *to,
+                }
+            }
+            Operation::MoveColumn { sheet_index, from, to, row_lens } => {
+                let mut rows = Vec::new();
+                if let Some(sheet) = file_data.sheets.get_mut(*sheet_index) {
+                    for (i, row) in sheet.rows.iter_mut().enumerate() {
+                        let len = row_lens.get(i).copied().unwrap_or(row.len());
+                        move_row_cell(row, *from, *to, len);
+                    }
+                    // 索引重建由调用方异步处理
+                    rows = sheet.rows.clone();
+                }
+                OperationResult::MoveColumn {
+                    sheet_index: *sheet_index,
+                    from: *from,
+                    to: *to,
+                    rows,
+                }
+            }
+            Operation::MoveSheet { from, to } => {
+                move_item(&mut file_data.sheets, *from, *to);
+                OperationResult::MoveSheet {
+                    from: *from,
+                    to: *to,
+                    active_sheet_index: (*to).min(file_data.sheets.len().saturating_sub(1)),
+                }
+            }
+            Operation::Group { operations } => OperationResult::Group {
+                results: operations.iter().map(|op| op.execute(file_data)).collect(),
+            },
         }
     }
 
@@ -445,33 +650,37 @@ impl Operation {
                     new_value: old_value.clone(),
                 }
             }
-            Operation::AddRow { sheet_index, row_index, row_data } => {
+            Operation::AddRow { sheet_index, row_index, row_data, row_height } => {
                 Operation::DeleteRow {
                     sheet_index: *sheet_index,
                     row_index: *row_index,
                     row_data: row_data.clone(), // 保留添加的行数据，用于撤销 DeleteRow 时恢复
+                    row_height: *row_height,
                 }
             }
-            Operation::DeleteRow { sheet_index, row_index, row_data } => {
+            Operation::DeleteRow { sheet_index, row_index, row_data, row_height } => {
                 Operation::AddRow {
                     sheet_index: *sheet_index,
                     row_index: *row_index,
                     row_data: row_data.clone(),
+                    row_height: *row_height,
                 }
             }
-            Operation::AddColumn { sheet_index, col_index, col_data } => {
+            // 追加的列（col_index 为 None）撤销时删除每一行的最后一个单元格
+            Operation::AddColumn { sheet_index, col_index, col_data, row_lens } => {
                 Operation::DeleteColumn {
                     sheet_index: *sheet_index,
-                    // 使用添加列时记录的索引
-                    col_index: col_index.unwrap_or(0),
+                    col_index: *col_index,
                     col_data: col_data.clone(),
+                    row_lens: row_lens.clone(),
                 }
             }
-            Operation::DeleteColumn { sheet_index, col_index, col_data } => {
+            Operation::DeleteColumn { sheet_index, col_index, col_data, row_lens } => {
                 Operation::AddColumn {
                     sheet_index: *sheet_index,
-                    col_index: Some(*col_index),
+                    col_index: *col_index,
                     col_data: col_data.clone(),
+                    row_lens: row_lens.clone(),
                 }
             }
             Operation::AddSheet { .. } => {
@@ -487,6 +696,7 @@ impl Operation {
                     name: sheet_data.name.clone(),
                     sheet_data: Some(sheet_data.clone()),
                     sheet_index: Some(*sheet_index), // 恢复到原始位置
+                    size: None,
                 }
             }
             // SortColumn 的 undo：用排序前的数据恢复（不需要反向操作，因为已保存原始数据）
@@ -502,6 +712,29 @@ impl Operation {
                     previous_sort_state: previous_sort_state.clone(),
                 }
             }
+            Operation::ResizeSheet { sheet_index, old_rows, old_row_heights, .. } => Operation::RestoreRows {
+                sheet_index: *sheet_index,
+                rows: old_rows.clone(),
+                row_heights: old_row_heights.clone(),
+            },
+            // RestoreRows 只作为撤销操作执行，不会进入历史记录
+            Operation::RestoreRows { .. } => self.clone(),
+            Operation::MoveRow { sheet_index, from, to } => Operation::MoveRow {
+                sheet_index: *sheet_index,
+                from: *to,
+                to: *from,
+            },
+            Operation::MoveColumn { sheet_index, from, to, row_lens } => Operation::MoveColumn {
+                sheet_index: *sheet_index,
+                from: *to,
+                to: *from,
+                row_lens: row_lens.clone(),
+            },
+            Operation::MoveSheet { from, to } => Operation::MoveSheet { from: *to, to: *from },
+            // Group 的撤销：逆序撤销每个子操作
+            Operation::Group { operations } => Operation::Group {
+                operations: operations.iter().rev().map(|op| op.create_undo_op()).collect(),
+            },
         }
     }
 }
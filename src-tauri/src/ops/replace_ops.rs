@@ -0,0 +1,221 @@
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::error::AppError;
+use crate::ops::bounds::{check_sheet, Bounds};
+use crate::state::editor_state::{EditorState, Operation};
+use crate::types::{CellValue, NewlineStyle, SheetData};
+
+/// 收集矩形区域内整格匹配（不区分大小写，与搜索一致）的单元格，生成对应的 SetCell 操作
+/// 区域超出 sheet 的部分自动忽略
+fn collect_replacements(
+    sheet: &SheetData,
+    sheet_index: usize,
+    find: &str,
+    replace: &CellValue,
+    rows: Range<usize>,
+    cols: Range<usize>,
+) -> Vec<Operation> {
+    let find = find.to_lowercase();
+    let mut operations = Vec::new();
+
+    let row_end = rows.end.min(sheet.rows.len());
+    for (row, r) in sheet.rows.iter().enumerate().take(row_end).skip(rows.start) {
+        let col_end = cols.end.min(r.len());
+        for (c, cell) in r.iter().enumerate().take(col_end).skip(cols.start) {
+            if cell == replace {
+                continue;
+            }
+            if cell.to_text().to_lowercase() == find {
+                operations.push(Operation::SetCell {
+                    sheet_index,
+                    row,
+                    col: c,
+                    old_value: cell.clone(),
+                    new_value: replace.clone(),
+                });
+            }
+        }
+    }
+
+    operations
+}
+
+/// 全部替换（整格匹配），可通过 col 限定在单列内
+/// 所有修改作为一个 Group 操作记录，一次撤销即可还原，返回修改的单元格数
+pub fn do_replace_all(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    find: String,
+    replace: CellValue,
+    col: Option<usize>,
+) -> Result<usize, AppError> {
+    if find.is_empty() {
+        return Ok(0);
+    }
+
+    let mut state = state.write().unwrap();
+    let editor_state = match state.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    if let Some(col) = col {
+        Bounds::of(sheet).check_col(col)?;
+    }
+
+    let cols = match col {
+        Some(col) => col..col + 1,
+        None => 0..usize::MAX,
+    };
+    let operations = collect_replacements(sheet, sheet_index, &find, &replace, 0..usize::MAX, cols);
+    let count = operations.len();
+    if count > 0 {
+        // SetCell 会增量更新索引，无需整表重建
+        editor_state.execute(Operation::Group { operations });
+    }
+
+    Ok(count)
+}
+
+/// 在矩形区域内替换（整格匹配），起止行列均包含在内，超出 sheet 的部分自动截断
+/// 所有修改作为一个 Group 操作记录，一次撤销即可还原，返回修改的单元格数
+#[allow(clippy::too_many_arguments)]
+pub fn do_replace_in_range(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    start_row: usize,
+    start_col: usize,
+    end_row: usize,
+    end_col: usize,
+    find: String,
+    replace: CellValue,
+) -> Result<usize, AppError> {
+    if find.is_empty() {
+        return Ok(0);
+    }
+
+    let mut state = state.write().unwrap();
+    let editor_state = match state.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let (top, bottom) = (start_row.min(end_row), start_row.max(end_row));
+    let (left, right) = (start_col.min(end_col), start_col.max(end_col));
+
+    let operations = collect_replacements(
+        sheet,
+        sheet_index,
+        &find,
+        &replace,
+        top..bottom.saturating_add(1),
+        left..right.saturating_add(1),
+    );
+    let count = operations.len();
+    if count > 0 {
+        // SetCell 会增量更新索引，无需整表重建
+        editor_state.execute(Operation::Group { operations });
+    }
+
+    Ok(count)
+}
+
+/// 将文本中的换行（\r\n、\n 以及单独的 \r）统一为指定风格，没有需要修改的换行时返回 None
+fn normalize_newlines(text: &str, style: NewlineStyle) -> Option<String> {
+    let newline = style.as_str();
+    let mut normalized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                normalized.push_str(newline);
+            }
+            '\n' => normalized.push_str(newline),
+            c => normalized.push(c),
+        }
+    }
+    (normalized != text).then_some(normalized)
+}
+
+/// 统一 sheet 中字符串单元格的换行风格（非字符串单元格不受影响）
+/// 所有修改作为一个 Group 操作记录，一次撤销即可还原，返回修改的单元格数
+pub fn do_normalize_newlines(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    style: NewlineStyle,
+) -> Result<usize, AppError> {
+    let mut state = state.write().unwrap();
+    let editor_state = match state.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let mut operations = Vec::new();
+    for (row, r) in sheet.rows.iter().enumerate() {
+        for (col, cell) in r.iter().enumerate() {
+            let CellValue::String(text) = cell else { continue };
+            if let Some(normalized) = normalize_newlines(text, style) {
+                operations.push(Operation::SetCell {
+                    sheet_index,
+                    row,
+                    col,
+                    old_value: cell.clone(),
+                    new_value: CellValue::String(normalized),
+                });
+            }
+        }
+    }
+
+    let count = operations.len();
+    if count > 0 {
+        // SetCell 会增量更新索引，无需整表重建
+        editor_state.execute(Operation::Group { operations });
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::editor_ops::{do_redo, do_undo};
+    use crate::ops::test_util::{rows, search, state_with, text};
+
+    #[test]
+    fn replace_all_is_one_undo_step() {
+        let sheet = vec![
+            vec![text("old"), text("keep"), text("OLD")],
+            vec![text("old"), text("old"), text("keep")],
+            vec![text("keep"), text("old"), text("older")],
+        ];
+        let state = state_with(vec![sheet.clone()]);
+        let replaced: Vec<Vec<CellValue>> = sheet
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| if cell.to_text().eq_ignore_ascii_case("old") { text("new") } else { cell.clone() })
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(do_replace_all(state.clone(), 0, "old".to_string(), text("new"), None).unwrap(), 5);
+        assert_eq!(rows(&state, 0), replaced);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), sheet);
+        assert!(search(&state, 0, "new").is_empty());
+        assert!(!state.read().unwrap().as_ref().unwrap().can_undo);
+
+        do_redo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), replaced);
+        assert_eq!(search(&state, 0, "new"), vec![(0, 0), (0, 2), (1, 0), (1, 1), (2, 1)]);
+    }
+}
@@ -1,44 +1,191 @@
+use std::collections::{BTreeSet, HashSet};
 use std::sync::Arc;
 use std::sync::RwLock;
 
 use crate::state::editor_state::EditorState;
 use crate::error::AppError;
-use crate::types::{SearchResult, SearchScope, CellValue};
-
-/// 将列索引转换为字母 (0 -> A, 1 -> B, ...)
-fn col_to_letter(col: usize) -> String {
-    let mut result = String::new();
-    let mut n = col;
-    while n >= 26 {
-        result.insert(0, char::from_u32((n % 26) as u32 + 65).unwrap());
-        n = n / 26 - 1;
+use crate::ops::a1::col_to_letter;
+use crate::ops::bounds::{check_sheet, Bounds};
+use crate::ops::index_ops::{ensure_indices_built, numeric_token, tokenize_words};
+use crate::types::{
+    CellPosition, CellValue, CellValueType, SearchCursor, SearchMode, SearchResult, SearchScope, SheetData, SheetIndex, SheetMatchCount,
+};
+
+/// 将查询转换为索引 token（与建索引时的规则一致）
+fn query_token(query: &str) -> String {
+    query.to_lowercase()
+}
+
+/// 在文本中查找与短语一致、且首尾位于词边界的位置（两者都应已转为小写），返回字节范围
+fn find_phrase(text: &str, phrase: &str) -> Option<(usize, usize)> {
+    let is_word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+
+    text.match_indices(phrase).map(|(start, _)| (start, start + phrase.len())).find(|&(start, end)| {
+        !is_word_char(text[..start].chars().next_back()) && !is_word_char(text[end..].chars().next())
+    })
+}
+
+/// 将 value 转小写后的字节位置换算为 value 中的字符位置
+/// 部分字符转小写后字符数会变化（如 'İ' 变为 "i̇"），不能按小写文本计数；
+/// 位置落在某个字符转小写的结果中间时，round_up 为 true 取该字符之后，否则取该字符之前
+fn original_char_offset(value: &str, lower_offset: usize, round_up: bool) -> usize {
+    let mut lower_end = 0;
+    for (i, c) in value.chars().enumerate() {
+        let lower_start = lower_end;
+        // 逐字符转小写的字节长度与 str::to_lowercase 一致（上下文相关的 Σ -> ς 与 σ 字节数相同）
+        lower_end += c.to_lowercase().map(char::len_utf8).sum::<usize>();
+        if lower_offset == lower_start {
+            return i;
+        }
+        if lower_offset < lower_end {
+            return if round_up { i + 1 } else { i };
+        }
     }
-    result.insert(0, char::from_u32(n as u32 + 65).unwrap());
-    result
+    value.chars().count()
 }
 
-/// 将单元格值转换为字符串
-fn cell_to_string(cell: &CellValue) -> String {
-    match cell {
-        CellValue::Null => String::new(),
-        CellValue::String(s) => s.clone(),
-        CellValue::Number(n) => n.to_string(),
-        CellValue::Boolean(b) => b.to_string(),
+/// 计算匹配部分在 value 中的字符范围（短语模式为短语位置，其它模式为整个值）
+fn match_span(value: &str, query: &str, mode: SearchMode) -> (usize, usize) {
+    let whole = (0, value.chars().count());
+    match mode {
+        SearchMode::Exact | SearchMode::Words => whole,
+        SearchMode::Phrase => match find_phrase(&value.to_lowercase(), &query_token(query.trim())) {
+            Some((start, end)) => (original_char_offset(value, start, false), original_char_offset(value, end, true)),
+            None => whole,
+        },
     }
 }
 
-/// 搜索单元格
+/// 按搜索模式在索引中查找候选单元格位置
+/// 短语模式只返回包含所有词的候选，需再用 matching_cells 逐格校验
+fn lookup(index: &SheetIndex, query: &str, mode: SearchMode) -> Vec<CellPosition> {
+    match mode {
+        SearchMode::Exact => {
+            let token = query_token(query);
+            let mut positions = index.inverted_index.get(&token).cloned().unwrap_or_default();
+            // 数字查询同时按规范数字形式查找（"1000.0" 能命中数字 1000），文本 "007" 仍按原文匹配
+            if let Some(number) = numeric_token(query).filter(|n| *n != token) {
+                if let Some(extra) = index.inverted_index.get(&number) {
+                    positions.extend(extra.iter().cloned());
+                    positions.sort_by_key(|p| (p.row, p.col));
+                }
+            }
+            positions
+        }
+        SearchMode::Words | SearchMode::Phrase => {
+            let words = tokenize_words(query, index.tokenizer);
+            let Some((first, rest)) = words.split_first() else {
+                return vec![];
+            };
+            let Some(candidates) = index.word_index.get(first) else {
+                return vec![];
+            };
+            // 其余每个词都必须命中同一单元格（AND）
+            let others: Vec<HashSet<&CellPosition>> = rest
+                .iter()
+                .map(|w| index.word_index.get(w).map(|p| p.iter().collect()).unwrap_or_default())
+                .collect();
+            candidates
+                .iter()
+                .filter(|pos| others.iter().all(|set| set.contains(pos)))
+                .cloned()
+                .collect()
+        }
+    }
+}
+
+/// 将索引快照中的位置对应到 sheet 的当前单元格
+/// 跳过已不在 sheet 范围内的位置，并按单元格的当前值重新校验：
+/// - 整格模式：文本与查询一致，或查询的规范数字形式与数字单元格一致
+///   （索引按文本建立，数字 5 与字符串 "5" 共用 token，查询 "5.0" 只应命中数字）
+/// - 按词模式：包含查询的所有词
+/// - 短语模式：短语连续出现
+fn matching_cells<'a>(
+    sheet: &'a SheetData,
+    positions: Vec<CellPosition>,
+    query: &str,
+    mode: SearchMode,
+) -> impl Iterator<Item = (CellPosition, &'a CellValue)> {
+    let token = query_token(query);
+    let number = numeric_token(query);
+    let words = tokenize_words(query, sheet.index.tokenizer);
+    let phrase = query_token(query.trim());
+    positions.into_iter().filter_map(move |pos| {
+        let cell = sheet.rows.get(pos.row).and_then(|r| r.get(pos.col))?;
+        let text = cell.to_text();
+        let matches = match mode {
+            SearchMode::Exact => {
+                query_token(&text) == token
+                    || (cell.value_type() == CellValueType::Number && number.as_deref() == Some(text.as_ref()))
+            }
+            SearchMode::Words => {
+                let cell_words = tokenize_words(&text, sheet.index.tokenizer);
+                words.iter().all(|w| cell_words.contains(w))
+            }
+            SearchMode::Phrase => find_phrase(&text.to_lowercase(), &phrase).is_some(),
+        };
+        matches.then_some((pos, cell))
+    })
+}
+
+/// 在读锁下克隆目标 sheet 的索引快照（Arc，开销很小），随后即可释放锁
+fn index_snapshots(
+    state: &Arc<RwLock<Option<EditorState>>>,
+    sheet_index: Option<usize>,
+) -> Result<Vec<(usize, Arc<SheetIndex>)>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    Ok(editor_state
+        .file_data
+        .sheets
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| sheet_index.is_none_or(|s| s == *i))
+        .map(|(i, sheet)| (i, Arc::clone(&sheet.index)))
+        .collect())
+}
+
+/// 在索引快照上查找，不持有锁；查找期间编辑和索引重建不会被阻塞
+fn lookup_snapshots(
+    state: &Arc<RwLock<Option<EditorState>>>,
+    sheet_index: Option<usize>,
+    query: &str,
+    mode: SearchMode,
+) -> Result<Vec<(usize, Vec<CellPosition>)>, AppError> {
+    ensure_indices_built(state, sheet_index);
+    Ok(index_snapshots(state, sheet_index)?
+        .into_iter()
+        .map(|(i, index)| (i, lookup(&index, query, mode)))
+        .collect())
+}
+
+/// 搜索单元格（首次搜索某个 sheet 时构建其索引）
+///
+/// 查找在索引快照上进行，之后才短暂获取读锁读取单元格值，因此结果可能略微落后于编辑：
+/// - 快照之后被修改的单元格按当前值重新校验，不再匹配的不会返回
+/// - 快照之后被删除的行列上的位置会被跳过
+/// - 后台重建尚未完成时，刚写入的内容可能暂时搜索不到（开启 sync_index 可避免）
 pub fn do_search(
     state: Arc<RwLock<Option<EditorState>>>,
     query: String,
     scope: SearchScope,
     current_sheet_index: Option<usize>,
+    mode: SearchMode,
 ) -> Result<Vec<SearchResult>, AppError> {
     if query.is_empty() {
         return Ok(vec![]);
     }
 
-    let token = query.to_lowercase();
+    let target = match scope {
+        SearchScope::CurrentSheet => Some(current_sheet_index.unwrap_or(0)),
+        SearchScope::AllSheets => None,
+    };
+    let matches = lookup_snapshots(&state, target, &query, mode)?;
+
     let state = state.read().unwrap();
 
     let editor_state = match state.as_ref() {
@@ -48,51 +195,224 @@ pub fn do_search(
 
     let mut results = Vec::new();
 
-    match scope {
-        SearchScope::CurrentSheet => {
-            let sheet_idx = current_sheet_index.unwrap_or(0);
-            if let Some(sheet) = editor_state.file_data.sheets.get(sheet_idx) {
-                if let Some(positions) = sheet.index.inverted_index.get(&token) {
-                    for pos in positions {
-                        let value = sheet.rows.get(pos.row)
-                            .and_then(|r| r.get(pos.col))
-                            .map(|c| cell_to_string(c))
-                            .unwrap_or_default();
-
-                        results.push(SearchResult {
-                            sheet_index: sheet_idx,
-                            sheet_name: sheet.name.clone(),
-                            row: pos.row,
-                            col: pos.col,
-                            value,
-                            cell_position: format!("{}{}", col_to_letter(pos.col), pos.row + 1),
-                        });
-                    }
-                }
-            }
-        }
-        SearchScope::AllSheets => {
-            for (sheet_idx, sheet) in editor_state.file_data.sheets.iter().enumerate() {
-                if let Some(positions) = sheet.index.inverted_index.get(&token) {
-                    for pos in positions {
-                        let value = sheet.rows.get(pos.row)
-                            .and_then(|r| r.get(pos.col))
-                            .map(|c| cell_to_string(c))
-                            .unwrap_or_default();
-
-                        results.push(SearchResult {
-                            sheet_index: sheet_idx,
-                            sheet_name: sheet.name.clone(),
-                            row: pos.row,
-                            col: pos.col,
-                            value,
-                            cell_position: format!("{}{}", col_to_letter(pos.col), pos.row + 1),
-                        });
-                    }
-                }
-            }
+    for (sheet_idx, positions) in matches {
+        let Some(sheet) = editor_state.file_data.sheets.get(sheet_idx) else {
+            continue;
+        };
+        for (pos, cell) in matching_cells(sheet, positions, &query, mode) {
+            let value = cell.to_text().into_owned();
+            let value_type = cell.value_type();
+            let (match_start, match_end) = match_span(&value, &query, mode);
+
+            results.push(SearchResult {
+                sheet_index: sheet_idx,
+                sheet_name: sheet.name.clone(),
+                row: pos.row,
+                col: pos.col,
+                value,
+                value_type,
+                cell_position: format!("{}{}", col_to_letter(pos.col), pos.row + 1),
+                match_start,
+                match_end,
+            });
         }
     }
 
     Ok(results)
 }
+
+/// 统计每个 sheet 的命中数（只读取索引，不构建完整结果；未构建的索引会先构建）
+/// 与 do_search 一样在索引快照上查找
+pub fn do_search_summary(
+    state: Arc<RwLock<Option<EditorState>>>,
+    query: String,
+    mode: SearchMode,
+) -> Result<Vec<SheetMatchCount>, AppError> {
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let matches = lookup_snapshots(&state, None, &query, mode)?;
+
+    let state = state.read().unwrap();
+
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    Ok(matches
+        .into_iter()
+        .filter_map(|(sheet_index, positions)| {
+            let sheet = editor_state.file_data.sheets.get(sheet_index)?;
+            Some(SheetMatchCount {
+                sheet_index,
+                sheet_name: sheet.name.clone(),
+                count: matching_cells(sheet, positions, &query, mode).count(),
+            })
+        })
+        .collect())
+}
+
+/// 列内自动补全：返回以 prefix 开头（不区分大小写）的不重复文本，排序后取前 limit 个（忽略表头和空单元格）
+pub fn do_suggest(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col: usize,
+    prefix: String,
+    limit: usize,
+) -> Result<Vec<String>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_col(col)?;
+
+    let prefix = query_token(&prefix);
+    let values: BTreeSet<String> = sheet
+        .rows
+        .iter()
+        .skip(sheet.data_start())
+        .filter_map(|row| row.get(col))
+        .map(CellValue::to_text)
+        .filter(|text| !text.is_empty() && query_token(text).starts_with(&prefix))
+        .map(|text| text.into_owned())
+        .collect();
+
+    Ok(values.into_iter().take(limit).collect())
+}
+
+/// 开始一次查找：计算匹配列表并保存为搜索游标，返回匹配数
+/// 之后用 search_next/search_prev 在列表中移动，列表不随编辑变化（位置在按键之间保持稳定）
+pub fn do_search_begin(
+    state: Arc<RwLock<Option<EditorState>>>,
+    query: String,
+    scope: SearchScope,
+    current_sheet_index: Option<usize>,
+    mode: SearchMode,
+) -> Result<usize, AppError> {
+    let results = do_search(state.clone(), query, scope, current_sheet_index, mode)?;
+    let count = results.len();
+
+    let mut state = state.write().unwrap();
+    match state.as_mut() {
+        Some(editor_state) => {
+            editor_state.search_cursor = Some(SearchCursor { results, position: None });
+            Ok(count)
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+/// 将搜索游标前移（forward）或后移一个匹配并返回该匹配，到达末尾/开头时回绕
+/// 没有匹配时返回 None；未调用 search_begin 时返回错误
+pub fn do_search_step(state: Arc<RwLock<Option<EditorState>>>, forward: bool) -> Result<Option<SearchResult>, AppError> {
+    let mut state = state.write().unwrap();
+    let editor_state = match state.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+    let Some(cursor) = editor_state.search_cursor.as_mut() else {
+        return Err(AppError::Internal("No search in progress".to_string()));
+    };
+
+    let len = cursor.results.len();
+    if len == 0 {
+        return Ok(None);
+    }
+    let position = match (cursor.position, forward) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(p), true) => (p + 1) % len,
+        (Some(p), false) => (p + len - 1) % len,
+    };
+    cursor.position = Some(position);
+
+    Ok(Some(cursor.results[position].clone()))
+}
+
+/// 结束查找，清除搜索游标
+pub fn do_search_end(state: Arc<RwLock<Option<EditorState>>>) -> Result<(), AppError> {
+    let mut state = state.write().unwrap();
+    match state.as_mut() {
+        Some(editor_state) => {
+            editor_state.search_cursor = None;
+            Ok(())
+        }
+        None => Err(AppError::Internal("No file loaded".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::cell_ops::do_set_cell;
+    use crate::ops::editor_ops::{do_redo, do_undo};
+    use crate::ops::test_util::{search, state_with, text};
+
+    type Hits = Vec<(usize, usize)>;
+
+    /// (0,0) 依次改为的值，以及改后查询 "5"、"5.0"、"true" 的命中（(0,1) 为字符串 "5"，(0,2) 为 true）
+    fn transitions() -> Vec<(CellValue, [Hits; 3])> {
+        let both = vec![(0, 0), (0, 1)];
+        let text_5 = vec![(0, 1)];
+        let true_only = vec![(0, 2)];
+        let both_true = vec![(0, 0), (0, 2)];
+        vec![
+            (CellValue::Integer(5), [both.clone(), vec![(0, 0)], true_only.clone()]),
+            (text("5"), [both.clone(), vec![], true_only.clone()]),
+            (CellValue::Boolean(true), [text_5.clone(), vec![], both_true.clone()]),
+            (text("TRUE"), [text_5.clone(), vec![], both_true]),
+            (CellValue::Number(5.5), [text_5.clone(), vec![], true_only.clone()]),
+            (CellValue::Null, [text_5, vec![], true_only.clone()]),
+            (CellValue::Number(5.0), [both, vec![(0, 0)], true_only]),
+        ]
+    }
+
+    fn assert_hits(state: &Arc<RwLock<Option<EditorState>>>, value: &CellValue, expected: &[Hits; 3]) {
+        for (query, hits) in ["5", "5.0", "true"].iter().zip(expected) {
+            assert_eq!(&search(state, 0, query), hits, "query {query:?} after setting {value:?}");
+        }
+    }
+
+    #[test]
+    fn search_follows_type_changes_at_the_same_cell() {
+        let steps = transitions();
+        let state = state_with(vec![vec![vec![steps[0].0.clone(), text("5"), CellValue::Boolean(true)]]]);
+        assert_hits(&state, &steps[0].0, &steps[0].1);
+
+        for (value, expected) in &steps[1..] {
+            do_set_cell(state.clone(), 0, 0, 0, CellValue::Null, value.clone()).unwrap();
+            assert_hits(&state, value, expected);
+        }
+
+        for (value, expected) in steps[..steps.len() - 1].iter().rev() {
+            do_undo(state.clone()).unwrap();
+            assert_hits(&state, value, expected);
+        }
+
+        for (value, expected) in &steps[1..] {
+            do_redo(state.clone()).unwrap();
+            assert_hits(&state, value, expected);
+        }
+    }
+
+    #[test]
+    fn phrase_span_is_in_original_characters() {
+        assert_eq!(match_span("Hello World", "world", SearchMode::Phrase), (6, 11));
+        // 'İ' 转小写后为两个字符，匹配位置仍按原文计
+        assert_eq!(match_span("İİ abc", "abc", SearchMode::Phrase), (3, 6));
+        assert_eq!(match_span("x İ y", "İ", SearchMode::Phrase), (2, 3));
+        assert_eq!(match_span("İSTANBUL is here", "is here", SearchMode::Phrase), (9, 16));
+    }
+
+    #[test]
+    fn whole_span_outside_phrase_mode() {
+        assert_eq!(match_span("İİ abc", "abc", SearchMode::Words), (0, 6));
+        assert_eq!(match_span("İİ abc", "İİ abc", SearchMode::Exact), (0, 6));
+        assert_eq!(match_span("abc", "xyz", SearchMode::Phrase), (0, 3));
+    }
+}
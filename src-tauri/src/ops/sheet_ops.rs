@@ -0,0 +1,542 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::error::AppError;
+use crate::ops::bounds::{check_sheet, check_sheet_size, Bounds};
+use crate::ops::index_ops::spawn_rebuild_sheet_index;
+use crate::ops::naming::{resolve_sheet_name, sanitize_sheet_name, unique_sheet_name};
+use crate::state::editor_state::{EditorState, Operation};
+use crate::types::{AggregateFunc, CellKey, CellValue, FreezePanes, OperationResult, RowRemoval, SheetData};
+
+/// 空值分组的 sheet 名称
+const BLANK_KEY_NAME: &str = "(blank)";
+
+/// 按列值将数据行分组（按首次出现顺序），返回 (键值, 行号列表)
+fn group_rows_by_column(rows: &[Vec<CellValue>], col: usize) -> Vec<(CellValue, Vec<usize>)> {
+    // 按 CellKey 分组：类型不同的值（如数字 1 与字符串 "1"）分开，所有 NaN 归为一组
+    let mut seen: HashMap<CellKey, usize> = HashMap::new();
+    let mut groups: Vec<(CellValue, Vec<usize>)> = Vec::new();
+
+    for (row_idx, row) in rows.iter().enumerate() {
+        let key = row.get(col).unwrap_or(&CellValue::Null);
+        match seen.get(&CellKey(key)) {
+            Some(&g) => groups[g].1.push(row_idx),
+            None => {
+                seen.insert(CellKey(key), groups.len());
+                groups.push((key.clone(), vec![row_idx]));
+            }
+        }
+    }
+
+    groups
+}
+
+/// 对一组值进行聚合，没有可聚合的值时返回 Null
+fn aggregate<'a>(values: impl Iterator<Item = &'a CellValue>, func: AggregateFunc) -> CellValue {
+    let values = values.map(CellValue::cached_value);
+
+    if let AggregateFunc::Count = func {
+        let count = values.filter(|v| !matches!(v, CellValue::Null)).count();
+        return CellValue::Integer(count as i64);
+    }
+
+    // 非数字值不参与数值聚合
+    let numbers: Vec<f64> = values.filter_map(CellValue::as_f64).collect();
+    if numbers.is_empty() {
+        return CellValue::Null;
+    }
+
+    let result = match func {
+        AggregateFunc::Sum => numbers.iter().sum(),
+        AggregateFunc::Avg => numbers.iter().sum::<f64>() / numbers.len() as f64,
+        AggregateFunc::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+        AggregateFunc::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        AggregateFunc::Count => unreachable!(),
+    };
+    CellValue::number(result)
+}
+
+/// 将生成的 sheet 作为可撤销的 AddSheet 添加到末尾（索引在首次搜索时构建）
+fn add_generated_sheet(
+    editor_state: &mut EditorState,
+    base_name: &str,
+    rows: Vec<Vec<CellValue>>,
+    has_header: bool,
+) -> OperationResult {
+    let name = unique_sheet_name(
+        editor_state.file_data.sheets.iter().map(|s| s.name.as_str()),
+        &sanitize_sheet_name(base_name),
+    );
+    let operation = Operation::AddSheet {
+        name: name.clone(),
+        sheet_data: Some(SheetData {
+            name,
+            rows,
+            merges: vec![],
+            visible: true,
+            has_header,
+            row_heights: Vec::new(),
+            freeze: None,
+            index: Arc::default(),
+        }),
+        sheet_index: None,
+        size: None,
+    };
+    editor_state.execute(operation)
+}
+
+/// 按指定列的不同值将 sheet 拆分为多个新 sheet（每个新 sheet 都带表头行）
+/// 所有新 sheet 作为一个 Group 操作添加，一次撤销即可全部移除；只有表头没有数据行时返回错误
+pub fn do_split_sheet_by_column(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col: usize,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_col(col)?;
+
+    let Some((header, data_rows)) = sheet.rows.split_first() else {
+        return Err(AppError::Internal("Sheet has no rows to split".to_string()));
+    };
+
+    let mut names: Vec<String> = editor_state.file_data.sheets.iter().map(|s| s.name.clone()).collect();
+
+    let operations: Vec<Operation> = group_rows_by_column(data_rows, col)
+        .into_iter()
+        .map(|(key, row_indices)| {
+            let base = match &key {
+                CellValue::Null => BLANK_KEY_NAME.to_string(),
+                other => sanitize_sheet_name(&other.to_text()),
+            };
+            let name = unique_sheet_name(names.iter().map(String::as_str), &base);
+            names.push(name.clone());
+
+            let mut rows = Vec::with_capacity(row_indices.len() + 1);
+            rows.push(header.clone());
+            rows.extend(row_indices.into_iter().map(|i| data_rows[i].clone()));
+
+            Operation::AddSheet {
+                name: name.clone(),
+                sheet_data: Some(SheetData {
+                    name,
+                    rows,
+                    merges: vec![],
+                    visible: true,
+                    has_header: true,
+                    row_heights: Vec::new(),
+                    freeze: None,
+                    index: Arc::default(),
+                }),
+                sheet_index: None,
+                size: None,
+            }
+        })
+        .collect();
+
+    // 空的 Group 会记录一个撤销后没有任何效果的步骤
+    if operations.is_empty() {
+        return Err(AppError::Internal("Sheet has no data rows to split".to_string()));
+    }
+
+    Ok(editor_state.execute(Operation::Group { operations }))
+}
+
+/// 按 key_col 分组并对 value_col 聚合，结果作为新的两列 sheet 添加（可撤销）
+/// 源 sheet 有表头时跳过表头，并将两列的表头带到结果 sheet 的第一行
+pub fn do_group_by(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    key_col: usize,
+    value_col: usize,
+    func: AggregateFunc,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let bounds = Bounds::of(sheet);
+    bounds.check_col(key_col)?;
+    bounds.check_col(value_col)?;
+
+    let (header, data_rows) = sheet.rows.split_at(sheet.data_start());
+    let mut rows: Vec<Vec<CellValue>> = header
+        .iter()
+        .map(|h| {
+            let title = |col: usize| h.get(col).cloned().unwrap_or(CellValue::Null);
+            vec![title(key_col), title(value_col)]
+        })
+        .collect();
+    rows.extend(group_rows_by_column(data_rows, key_col).into_iter().map(|(key, row_indices)| {
+        let values = row_indices
+            .iter()
+            .map(|&i| data_rows[i].get(value_col).unwrap_or(&CellValue::Null));
+        vec![key, aggregate(values, func)]
+    }));
+
+    let base_name = format!("{} Grouped", sheet.name);
+    let has_header = sheet.has_header;
+    Ok(add_generated_sheet(editor_state, &base_name, rows, has_header))
+}
+
+/// 生成透视表：row_col 的不同值作为行，col_col 的不同值作为列，单元格为 value_col 的聚合值
+/// 没有数据的组合为 Null，结果作为新 sheet 添加（可撤销，源 sheet 的表头行不参与透视）
+pub fn do_pivot(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    row_col: usize,
+    col_col: usize,
+    value_col: usize,
+    func: AggregateFunc,
+) -> Result<OperationResult, AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    let bounds = Bounds::of(sheet);
+    bounds.check_col(row_col)?;
+    bounds.check_col(col_col)?;
+    bounds.check_col(value_col)?;
+
+    // 记录每一行所属的列分组
+    let data_rows = &sheet.rows[sheet.data_start()..];
+    let col_groups = group_rows_by_column(data_rows, col_col);
+    let mut col_group_of_row = vec![0; data_rows.len()];
+    for (g, (_, row_indices)) in col_groups.iter().enumerate() {
+        for &i in row_indices {
+            col_group_of_row[i] = g;
+        }
+    }
+
+    // 表头：左上角为空，其余为列键
+    let mut rows: Vec<Vec<CellValue>> = Vec::new();
+    let mut header = vec![CellValue::Null];
+    header.extend(col_groups.iter().map(|(key, _)| key.clone()));
+    rows.push(header);
+
+    for (row_key, row_indices) in group_rows_by_column(data_rows, row_col) {
+        let mut cells: Vec<Vec<&CellValue>> = vec![Vec::new(); col_groups.len()];
+        for i in row_indices {
+            cells[col_group_of_row[i]].push(data_rows[i].get(value_col).unwrap_or(&CellValue::Null));
+        }
+
+        let mut row = vec![row_key];
+        row.extend(cells.into_iter().map(|values| {
+            if values.is_empty() {
+                CellValue::Null
+            } else {
+                aggregate(values.into_iter(), func)
+            }
+        }));
+        rows.push(row);
+    }
+
+    let base_name = format!("{} Pivot", sheet.name);
+    Ok(add_generated_sheet(editor_state, &base_name, rows, true))
+}
+
+/// 设置 sheet 的第一行是否为表头
+pub fn do_set_has_header(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    has_header: bool,
+) -> Result<(), AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    check_sheet(&editor_state.file_data, sheet_index)?;
+    editor_state.file_data.sheets[sheet_index].has_header = has_header;
+    Ok(())
+}
+
+/// 设置冻结窗格（顶部 rows 行、左侧 cols 列，保存为 xlsx 时应用），两者都为 0 时取消冻结
+pub fn do_set_freeze(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    rows: u32,
+    cols: u16,
+) -> Result<(), AppError> {
+    let mut state_guard = state.write().unwrap();
+    let editor_state = match state_guard.as_mut() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    check_sheet(&editor_state.file_data, sheet_index)?;
+    editor_state.file_data.sheets[sheet_index].freeze = (rows > 0 || cols > 0).then_some(FreezePanes { rows, cols });
+    Ok(())
+}
+
+/// 按名称查找 sheet 索引（不区分大小写），找不到或有歧义时返回错误
+pub fn do_resolve_sheet(state: Arc<RwLock<Option<EditorState>>>, name: String) -> Result<usize, AppError> {
+    let state_guard = state.read().unwrap();
+    let editor_state = match state_guard.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    resolve_sheet_name(editor_state.file_data.sheets.iter().map(|s| s.name.as_str()), &name)
+}
+
+/// 将 sheet 调整为 rows × cols：多出的行列截断（连同行高），不足的用 Null 补齐（一次撤销即可还原，包括被截断的数据）
+pub fn do_resize_sheet(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    rows: usize,
+    cols: usize,
+) -> Result<OperationResult, AppError> {
+    let result = {
+        let mut state_guard = state.write().unwrap();
+        let editor_state = match state_guard.as_mut() {
+            Some(s) => s,
+            None => return Err(AppError::Internal("No file loaded".to_string())),
+        };
+
+        let (rows, cols) = check_sheet_size((rows, cols))?;
+        let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+        let (old_rows, old_row_heights) = (sheet.rows.clone(), sheet.row_heights.clone());
+        editor_state.execute(Operation::ResizeSheet { sheet_index, rows, cols, old_rows, old_row_heights })
+    };
+
+    // 整表重建一次索引
+    spawn_rebuild_sheet_index(sheet_index, state);
+
+    Ok(result)
+}
+
+/// 删除 sheet 中的指定行（升序行号），preview 为 true 时只返回行号，不修改数据和撤销历史
+/// 实际删除时从下往上逐行删除，作为一个 Group 操作，一次撤销即可全部恢复
+fn remove_rows(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    find_rows: impl FnOnce(&SheetData) -> Result<Vec<usize>, AppError>,
+    preview: bool,
+) -> Result<RowRemoval, AppError> {
+    let (rows, result) = {
+        let mut state_guard = state.write().unwrap();
+        let editor_state = match state_guard.as_mut() {
+            Some(s) => s,
+            None => return Err(AppError::Internal("No file loaded".to_string())),
+        };
+
+        let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+        let rows = find_rows(sheet)?;
+        if preview || rows.is_empty() {
+            return Ok(RowRemoval { count: rows.len(), rows, result: None });
+        }
+
+        let operations = rows
+            .iter()
+            .rev()
+            .map(|&row_index| Operation::DeleteRow {
+                sheet_index,
+                row_index,
+                row_data: sheet.rows[row_index].clone(),
+                row_height: sheet.row_height(row_index),
+            })
+            .collect();
+        let result = editor_state.execute(Operation::Group { operations });
+        (rows, result)
+    };
+
+    // 整表重建一次索引
+    spawn_rebuild_sheet_index(sheet_index, state);
+
+    Ok(RowRemoval { count: rows.len(), rows, result: Some(result) })
+}
+
+/// 删除重复的数据行：cols 中各列的值都与前面某行相同时删除该行（保留首次出现的行，忽略表头）
+/// cols 为空时比较整行；不规则行中缺失的单元格视为 Null
+pub fn do_remove_duplicates(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    cols: Vec<usize>,
+    preview: bool,
+) -> Result<RowRemoval, AppError> {
+    let find_rows = |sheet: &SheetData| {
+        let bounds = Bounds::of(sheet);
+        for &col in &cols {
+            bounds.check_col(col)?;
+        }
+        let cols: Vec<usize> = if cols.is_empty() { (0..bounds.cols).collect() } else { cols };
+        let mut seen: HashSet<Vec<CellKey>> = HashSet::new();
+        Ok(sheet
+            .rows
+            .iter()
+            .enumerate()
+            .skip(sheet.data_start())
+            .filter(|(_, row)| {
+                let key = cols.iter().map(|&c| CellKey(row.get(c).unwrap_or(&CellValue::Null))).collect();
+                !seen.insert(key)
+            })
+            .map(|(row_index, _)| row_index)
+            .collect())
+    };
+
+    remove_rows(state, sheet_index, find_rows, preview)
+}
+
+/// 删除空行（所有单元格都为 Null 或空字符串的数据行，忽略表头）
+pub fn do_remove_empty_rows(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    preview: bool,
+) -> Result<RowRemoval, AppError> {
+    let is_empty = |cell: &CellValue| match cell {
+        CellValue::Null => true,
+        CellValue::String(s) => s.is_empty(),
+        _ => false,
+    };
+    let find_rows = |sheet: &SheetData| {
+        Ok(sheet
+            .rows
+            .iter()
+            .enumerate()
+            .skip(sheet.data_start())
+            .filter(|(_, row)| row.iter().all(is_empty))
+            .map(|(row_index, _)| row_index)
+            .collect())
+    };
+
+    remove_rows(state, sheet_index, find_rows, preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::editor_ops::{do_redo, do_undo};
+    use crate::ops::test_util::{rows, state_with, text};
+
+    fn row_heights(state: &Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Vec<Option<f64>> {
+        state.read().unwrap().as_ref().unwrap().file_data.sheets[sheet_index].row_heights.clone()
+    }
+
+    fn can_undo(state: &Arc<RwLock<Option<EditorState>>>) -> bool {
+        state.read().unwrap().as_ref().unwrap().can_undo
+    }
+
+    /// 第 1、3 行与第 0 行重复，第 2、4 行为空行
+    fn removal_sheet() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![text("a"), CellValue::Integer(1)],
+            vec![text("a"), CellValue::Integer(1)],
+            vec![CellValue::Null, text("")],
+            vec![text("a"), CellValue::Integer(1)],
+            vec![],
+            vec![text("a"), text("1")],
+        ]
+    }
+
+    #[test]
+    fn remove_rows_preview_changes_nothing() {
+        let state = state_with(vec![removal_sheet()]);
+
+        let duplicates = do_remove_duplicates(state.clone(), 0, vec![], true).unwrap();
+        assert_eq!((duplicates.rows, duplicates.count), (vec![1, 3], 2));
+        assert!(duplicates.result.is_none());
+
+        let empty = do_remove_empty_rows(state.clone(), 0, true).unwrap();
+        assert_eq!((empty.rows, empty.count), (vec![2, 4], 2));
+        assert!(empty.result.is_none());
+
+        assert_eq!(rows(&state, 0), removal_sheet());
+        assert!(!can_undo(&state));
+    }
+
+    #[test]
+    fn remove_rows_is_one_undo_step() {
+        let state = state_with(vec![removal_sheet()]);
+
+        let removal = do_remove_duplicates(state.clone(), 0, vec![], false).unwrap();
+        assert_eq!(removal.rows, [1, 3]);
+        assert!(removal.result.is_some());
+        let mut expected = removal_sheet();
+        expected.remove(3);
+        expected.remove(1);
+        assert_eq!(rows(&state, 0), expected);
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), removal_sheet());
+        assert!(!can_undo(&state));
+
+        do_remove_empty_rows(state.clone(), 0, false).unwrap();
+        assert_eq!(rows(&state, 0).len(), 4);
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), removal_sheet());
+        assert!(!can_undo(&state));
+    }
+
+    #[test]
+    fn split_without_data_rows_records_nothing() {
+        let state = state_with(vec![vec![vec![text("key"), text("value")]]]);
+        assert!(do_split_sheet_by_column(state.clone(), 0, 0).is_err());
+
+        let state = state.read().unwrap();
+        let editor_state = state.as_ref().unwrap();
+        assert!(!editor_state.can_undo);
+        assert_eq!(editor_state.file_data.sheets.len(), 1);
+    }
+
+    #[test]
+    fn group_rows_keys_on_cell_key() {
+        let rows = vec![
+            vec![CellValue::Number(f64::NAN)],
+            vec![CellValue::Integer(1)],
+            vec![CellValue::Number(f64::NAN)],
+            vec![text("1")],
+            vec![CellValue::Number(-0.0)],
+            vec![CellValue::Number(0.0)],
+            vec![],
+        ];
+        let groups: Vec<Vec<usize>> = group_rows_by_column(&rows, 0).into_iter().map(|(_, rows)| rows).collect();
+        assert_eq!(groups, [vec![0, 2], vec![1], vec![3], vec![4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn resize_truncates_and_restores_row_heights() {
+        let sheet: Vec<Vec<CellValue>> = (0..4).map(|r| vec![text(&r.to_string())]).collect();
+        let state = state_with(vec![sheet.clone()]);
+        let heights = vec![Some(20.0), None, Some(30.0), Some(40.0)];
+        state.write().unwrap().as_mut().unwrap().file_data.sheets[0].row_heights = heights.clone();
+
+        let result = do_resize_sheet(state.clone(), 0, 2, 1).unwrap();
+        assert_eq!(rows(&state, 0), sheet[..2]);
+        assert_eq!(row_heights(&state, 0), [Some(20.0), None]);
+        match result {
+            OperationResult::ResizeSheet { row_heights, .. } => assert_eq!(row_heights, [Some(20.0), None]),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        do_undo(state.clone()).unwrap();
+        assert_eq!(rows(&state, 0), sheet);
+        assert_eq!(row_heights(&state, 0), heights);
+
+        do_redo(state.clone()).unwrap();
+        assert_eq!(row_heights(&state, 0), [Some(20.0), None]);
+    }
+
+    #[test]
+    fn growing_a_sheet_keeps_row_heights() {
+        let state = state_with(vec![vec![vec![text("a")]]]);
+        state.write().unwrap().as_mut().unwrap().file_data.sheets[0].row_heights = vec![Some(25.0)];
+
+        do_resize_sheet(state.clone(), 0, 3, 2).unwrap();
+        assert_eq!(rows(&state, 0).len(), 3);
+        assert_eq!(row_heights(&state, 0), [Some(25.0)]);
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use crate::ops::bounds::{check_sheet, Bounds};
 use crate::ops::index_ops::spawn_rebuild_sheet_index;
 use crate::error::AppError;
 use crate::state::editor_state::{EditorState, Operation};
@@ -18,11 +19,10 @@ pub fn do_sort_column(
         let mut state = state.write().unwrap();
         match state.as_mut() {
             Some(editor_state) => {
+                let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+                Bounds::of(sheet).check_col(col_index)?;
                 // 获取当前 sheet 数据（排序前）
-                let old_sheet_data = editor_state.file_data.sheets
-                    .get(sheet_index)
-                    .cloned()
-                    .unwrap_or_default();
+                let old_sheet_data = sheet.clone();
 
                 let operation = Operation::SortColumn {
                     sheet_index,
@@ -52,3 +52,44 @@ pub fn do_sort_column(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::sheet_ops::do_set_has_header;
+    use crate::ops::test_util::{rows, state_with, text};
+    use crate::types::CellValue;
+
+    fn sheet() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![text("name"), text("score")],
+            vec![text("b"), CellValue::Integer(2)],
+            vec![text("c"), CellValue::Integer(3)],
+            vec![text("a"), CellValue::Integer(1)],
+        ]
+    }
+
+    fn column(rows: &[Vec<CellValue>], col: usize) -> Vec<String> {
+        rows.iter().map(|r| r[col].to_text().into_owned()).collect()
+    }
+
+    #[test]
+    fn header_stays_pinned_after_sort() {
+        let state = state_with(vec![sheet()]);
+        do_set_has_header(state.clone(), 0, true).unwrap();
+
+        do_sort_column(state.clone(), 0, 0, true, None).unwrap();
+        assert_eq!(column(&rows(&state, 0), 0), ["name", "a", "b", "c"]);
+
+        do_sort_column(state.clone(), 0, 1, false, None).unwrap();
+        assert_eq!(column(&rows(&state, 0), 0), ["name", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn without_header_every_row_is_sorted() {
+        let state = state_with(vec![sheet()]);
+
+        do_sort_column(state.clone(), 0, 0, true, None).unwrap();
+        assert_eq!(column(&rows(&state, 0), 0), ["a", "b", "c", "name"]);
+    }
+}
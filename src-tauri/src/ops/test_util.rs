@@ -0,0 +1,62 @@
+//! 测试辅助函数
+
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use crate::ops::search_ops::do_search;
+use crate::state::editor_state::EditorState;
+use crate::types::{CellValue, FileData, SearchMode, SearchScope, SheetData};
+
+/// 字符串单元格
+pub fn text(s: &str) -> CellValue {
+    CellValue::String(s.to_string())
+}
+
+/// 由各 sheet 的行数据构建文件数据（sheet 名为 Sheet1、Sheet2……）
+pub fn file_data(sheets: Vec<Vec<Vec<CellValue>>>) -> FileData {
+    FileData {
+        file_name: "test.xlsx".to_string(),
+        sheets: sheets
+            .into_iter()
+            .enumerate()
+            .map(|(i, rows)| SheetData {
+                name: format!("Sheet{}", i + 1),
+                rows,
+                ..SheetData::default()
+            })
+            .collect(),
+        delimiter: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// 由各 sheet 的行数据构建编辑器状态（开启 sync_index，操作返回后索引即为最新）
+pub fn state_with(sheets: Vec<Vec<Vec<CellValue>>>) -> Arc<RwLock<Option<EditorState>>> {
+    let mut editor_state = EditorState::new(file_data(sheets));
+    editor_state.sync_index = true;
+    Arc::new(RwLock::new(Some(editor_state)))
+}
+
+/// 整格搜索指定 sheet，返回命中的 (行, 列)，按位置排序
+pub fn search(state: &Arc<RwLock<Option<EditorState>>>, sheet_index: usize, query: &str) -> Vec<(usize, usize)> {
+    search_mode(state, sheet_index, query, SearchMode::Exact)
+}
+
+/// 按指定模式搜索指定 sheet，返回命中的 (行, 列)，按位置排序
+pub fn search_mode(
+    state: &Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    query: &str,
+    mode: SearchMode,
+) -> Vec<(usize, usize)> {
+    let results = do_search(state.clone(), query.to_string(), SearchScope::CurrentSheet, Some(sheet_index), mode).unwrap();
+    let mut positions: Vec<(usize, usize)> = results.iter().map(|r| (r.row, r.col)).collect();
+    positions.sort_unstable();
+    positions
+}
+
+/// 指定 sheet 当前的行数据
+pub fn rows(state: &Arc<RwLock<Option<EditorState>>>, sheet_index: usize) -> Vec<Vec<CellValue>> {
+    let state = state.read().unwrap();
+    state.as_ref().unwrap().file_data.sheets[sheet_index].rows.clone()
+}
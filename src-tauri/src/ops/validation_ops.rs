@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use regex::Regex;
+
+use crate::error::AppError;
+use crate::ops::bounds::{check_sheet, Bounds, XLSX_MAX_COLS, XLSX_MAX_ROWS};
+use crate::state::editor_state::EditorState;
+use crate::types::{
+    CellKey, CellPosition, CellValue, FileData, Issue, IssueKind, ValidationRule, DEFAULT_MAX_CELL_LEN,
+};
+
+/// 按规则校验指定列，返回不符合规则的单元格位置（只读）
+pub fn do_validate_column(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col: usize,
+    rule: ValidationRule,
+) -> Result<Vec<CellPosition>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_col(col)?;
+
+    let regex = match &rule {
+        ValidationRule::Matches(pattern) => Some(
+            Regex::new(pattern).map_err(|e| AppError::InvalidPattern(e.to_string()))?,
+        ),
+        _ => None,
+    };
+
+    let violations = sheet
+        .rows
+        .iter()
+        .enumerate()
+        .filter_map(|(row, r)| {
+            // 不规则行中缺失的单元格按 Null 处理
+            // 公式单元格按缓存结果校验
+            let cell = r.get(col).unwrap_or(&CellValue::Null).cached_value();
+            let valid = match (&rule, cell) {
+                (ValidationRule::NonEmpty, CellValue::Null) => false,
+                (ValidationRule::NonEmpty, CellValue::String(s)) => !s.is_empty(),
+                (ValidationRule::NonEmpty, _) => true,
+                (_, CellValue::Null) => true,
+                (ValidationRule::IsNumber, c) => c.as_f64().is_some(),
+                (ValidationRule::IsBoolean, c) => matches!(c, CellValue::Boolean(_)),
+                (ValidationRule::Matches(_), c) => {
+                    regex.as_ref().is_some_and(|re| re.is_match(&c.to_text()))
+                }
+            };
+            (!valid).then_some(CellPosition { row, col })
+        })
+        .collect();
+
+    Ok(violations)
+}
+
+/// 查找列中的重复值，返回位置分组（每组至少两个单元格，按首次出现顺序，忽略 Null 和表头）
+pub fn do_find_duplicates(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+    col: usize,
+) -> Result<Vec<Vec<CellPosition>>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+    Bounds::of(sheet).check_col(col)?;
+
+    // 按 CellKey 分组，类型不同的值（如数字 1 与字符串 "1"）不算重复
+    let mut seen: HashMap<CellKey, usize> = HashMap::new();
+    let mut groups: Vec<Vec<CellPosition>> = Vec::new();
+
+    for (row, r) in sheet.rows.iter().enumerate().skip(sheet.data_start()) {
+        let cell = match r.get(col) {
+            Some(CellValue::Null) | None => continue,
+            Some(cell) => cell,
+        };
+
+        match seen.get(&CellKey(cell)) {
+            Some(&g) => groups[g].push(CellPosition { row, col }),
+            None => {
+                seen.insert(CellKey(cell), groups.len());
+                groups.push(vec![CellPosition { row, col }]);
+            }
+        }
+    }
+
+    Ok(groups.into_iter().filter(|positions| positions.len() > 1).collect())
+}
+
+/// 查找看起来为空、实际是空串或纯空白字符串的单元格（与 Null 区分，只读）
+pub fn do_find_blank_like(
+    state: Arc<RwLock<Option<EditorState>>>,
+    sheet_index: usize,
+) -> Result<Vec<CellPosition>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    let sheet = check_sheet(&editor_state.file_data, sheet_index)?;
+
+    Ok(sheet
+        .rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row, r)| {
+            r.iter().enumerate().filter_map(move |(col, cell)| match cell {
+                CellValue::String(s) if s.trim().is_empty() => Some(CellPosition { row, col }),
+                _ => None,
+            })
+        })
+        .collect())
+}
+
+/// 导出前的整体检查：不规则行、非有限数字、超长文本、重名 sheet、超出 xlsx 行列上限（只读）
+/// 问题按 sheet 顺序返回，同一 sheet 内按行列顺序
+pub fn do_validate_workbook(state: Arc<RwLock<Option<EditorState>>>) -> Result<Vec<Issue>, AppError> {
+    let state = state.read().unwrap();
+    let editor_state = match state.as_ref() {
+        Some(s) => s,
+        None => return Err(AppError::Internal("No file loaded".to_string())),
+    };
+
+    Ok(validate_workbook(&editor_state.file_data))
+}
+
+fn validate_workbook(file_data: &FileData) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut names = HashSet::new();
+
+    for (sheet_index, sheet) in file_data.sheets.iter().enumerate() {
+        let issue = |position: Option<CellPosition>, kind: IssueKind, message: String| Issue {
+            sheet: sheet_index,
+            position,
+            kind,
+            message,
+        };
+
+        if !names.insert(sheet.name.to_lowercase()) {
+            issues.push(issue(
+                None,
+                IssueKind::DuplicateSheetName,
+                format!("Sheet name '{}' is already used by another sheet", sheet.name),
+            ));
+        }
+
+        let bounds = Bounds::of(sheet);
+        if bounds.rows > XLSX_MAX_ROWS {
+            issues.push(issue(
+                Some(CellPosition { row: XLSX_MAX_ROWS, col: 0 }),
+                IssueKind::ExceedsXlsxLimit,
+                format!("{} rows exceed the xlsx limit of {}", bounds.rows, XLSX_MAX_ROWS),
+            ));
+        }
+        if bounds.cols > XLSX_MAX_COLS {
+            issues.push(issue(
+                Some(CellPosition { row: 0, col: XLSX_MAX_COLS }),
+                IssueKind::ExceedsXlsxLimit,
+                format!("{} columns exceed the xlsx limit of {}", bounds.cols, XLSX_MAX_COLS),
+            ));
+        }
+
+        for (row, r) in sheet.rows.iter().enumerate() {
+            if r.len() < bounds.cols {
+                issues.push(issue(
+                    Some(CellPosition { row, col: r.len() }),
+                    IssueKind::RaggedRow,
+                    format!("Row has {} cells, the widest row has {}", r.len(), bounds.cols),
+                ));
+            }
+
+            for (col, cell) in r.iter().enumerate() {
+                // 公式和带格式的数字按实际值检查
+                match cell.cached_value() {
+                    CellValue::Number(n) if !n.is_finite() => issues.push(issue(
+                        Some(CellPosition { row, col }),
+                        IssueKind::NonFiniteNumber,
+                        format!("Number {} cannot be stored in xlsx", n),
+                    )),
+                    CellValue::String(s) if s.len() > DEFAULT_MAX_CELL_LEN => {
+                        let len = s.chars().count();
+                        if len > DEFAULT_MAX_CELL_LEN {
+                            issues.push(issue(
+                                Some(CellPosition { row, col }),
+                                IssueKind::OversizedCell,
+                                format!("Text has {} characters, the xlsx limit is {}", len, DEFAULT_MAX_CELL_LEN),
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::test_util::{file_data, state_with, text};
+
+    fn positions(positions: &[CellPosition]) -> Vec<(usize, usize)> {
+        positions.iter().map(|p| (p.row, p.col)).collect()
+    }
+
+    /// 第 1 列依次为 1、"x"、Null、缺失（不规则行）、""、true、"42"
+    fn column_sheet() -> Vec<Vec<CellValue>> {
+        vec![
+            vec![text("a"), CellValue::Integer(1)],
+            vec![text("b"), text("x")],
+            vec![text("c"), CellValue::Null],
+            vec![text("d")],
+            vec![text("e"), text("")],
+            vec![text("f"), CellValue::Boolean(true)],
+            vec![text("g"), text("42")],
+        ]
+    }
+
+    fn validate(rule: ValidationRule) -> Vec<(usize, usize)> {
+        let state = state_with(vec![column_sheet()]);
+        positions(&do_validate_column(state, 0, 1, rule).unwrap())
+    }
+
+    #[test]
+    fn null_and_missing_cells_only_fail_non_empty() {
+        assert_eq!(validate(ValidationRule::NonEmpty), [(2, 1), (3, 1), (4, 1)]);
+        assert_eq!(validate(ValidationRule::IsBoolean), [(0, 1), (1, 1), (4, 1), (6, 1)]);
+        // 非文本按显示文本匹配：true 的文本 "true" 符合规则
+        assert_eq!(validate(ValidationRule::Matches("^[a-z]+$".to_string())), [(0, 1), (4, 1), (6, 1)]);
+    }
+
+    #[test]
+    fn is_number_rejects_text() {
+        assert_eq!(validate(ValidationRule::IsNumber), [(1, 1), (4, 1), (5, 1), (6, 1)]);
+    }
+
+    #[test]
+    fn invalid_regex_is_a_pattern_error() {
+        let state = state_with(vec![column_sheet()]);
+        let error = do_validate_column(state, 0, 1, ValidationRule::Matches("(".to_string())).unwrap_err();
+        assert_eq!(error.code(), "INVALID_PATTERN");
+    }
+
+    #[test]
+    fn find_duplicates_keeps_types_apart() {
+        let state = state_with(vec![vec![
+            vec![CellValue::Integer(1)],
+            vec![text("1")],
+            vec![CellValue::Null],
+            vec![CellValue::Integer(1)],
+            vec![CellValue::Number(1.5)],
+            vec![text("1")],
+            vec![],
+            vec![CellValue::Number(f64::NAN)],
+            vec![CellValue::Number(f64::NAN)],
+        ]]);
+        let groups: Vec<Vec<(usize, usize)>> =
+            do_find_duplicates(state, 0, 0).unwrap().iter().map(|g| positions(g)).collect();
+        assert_eq!(groups, [vec![(0, 0), (3, 0)], vec![(1, 0), (5, 0)], vec![(7, 0), (8, 0)]]);
+    }
+
+    #[test]
+    fn find_blank_like_skips_null_and_text() {
+        let state = state_with(vec![vec![vec![text(""), CellValue::Null, text(" \t")], vec![text(" x ")]]]);
+        assert_eq!(positions(&do_find_blank_like(state, 0).unwrap()), [(0, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn workbook_issues_in_sheet_order() {
+        let mut data = file_data(vec![
+            vec![vec![text("a"), text("b")], vec![text("c")], vec![CellValue::Number(f64::INFINITY), text("d")]],
+            vec![vec![text("e")]],
+        ]);
+        data.sheets[1].name = "SHEET1".to_string();
+        let kinds: Vec<(usize, IssueKind)> = validate_workbook(&data).into_iter().map(|i| (i.sheet, i.kind)).collect();
+        assert_eq!(
+            kinds,
+            [(0, IssueKind::RaggedRow), (0, IssueKind::NonFiniteNumber), (1, IssueKind::DuplicateSheetName)]
+        );
+    }
+}
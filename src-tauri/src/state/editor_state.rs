@@ -1,27 +1,125 @@
 use serde::{Deserialize, Serialize};
-use crate::types::{CellValue, FileData, OperationResult};
-pub use crate::ops::operation::{Operation, Undoable};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use crate::ops::index_ops::rebuild_sheet_index;
+use crate::types::{CellValue, FileData, OperationAction, OperationResult, SearchCursor, Tokenizer};
+pub use crate::ops::operation::{Operation, Undoable, DEFAULT_SHEET_SIZE};
 
 /// 编辑器状态管理器
+/// 撤销/重做历史参与序列化，加载时由 SavedEditorState 重新计算 can_undo/can_redo
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedEditorState")]
 pub struct EditorState {
     pub file_data: FileData,
-    #[serde(skip)]
     pub history: Vec<Operation>,
-    #[serde(skip)]
     pub redo_stack: Vec<Operation>,
     pub can_undo: bool,
     pub can_redo: bool,
+    /// 是否同步重建索引（开启后批量操作返回时索引已是最新，搜索结果可确定地反映最近一次编辑）
+    #[serde(default)]
+    pub sync_index: bool,
+    /// 新建 sheet 的默认 (行数, 列数)
+    #[serde(default = "default_sheet_size")]
+    pub default_sheet_size: (usize, usize),
+    /// 添加行/列时新单元格的值（默认 Null）
+    #[serde(default = "default_cell")]
+    pub default_cell: CellValue,
+    /// 数据版本号，每次执行/撤销/重做后更新（后台构建索引时据此判断数据是否已被修改）
+    #[serde(skip, default = "next_revision")]
+    pub revision: u64,
+    /// 加载或上次保存时 file_data 的内容哈希（用于按内容判断是否有未保存的修改）
+    #[serde(skip)]
+    pub saved_hash: u64,
+    /// 按词索引的分词方式（修改后索引需重建）
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
+    /// 查找栏的搜索游标（search_begin 创建，search_end 清除）
+    #[serde(skip)]
+    pub search_cursor: Option<SearchCursor>,
+}
+
+fn default_sheet_size() -> (usize, usize) {
+    DEFAULT_SHEET_SIZE
+}
+
+fn default_cell() -> CellValue {
+    CellValue::Null
+}
+
+/// 序列化后的编辑器状态（can_undo/can_redo 由历史推导，不信任保存的值）
+/// SheetIndex 不参与序列化：加载后 file_data 和历史中保存的 sheet 的索引均为未构建状态，
+/// 首次搜索时构建（开启 sync_index 时立即构建），撤销 DeleteSheet 恢复的 sheet 同样可搜索
+#[derive(Deserialize)]
+struct SavedEditorState {
+    file_data: FileData,
+    #[serde(default)]
+    history: Vec<Operation>,
+    #[serde(default)]
+    redo_stack: Vec<Operation>,
+    #[serde(default)]
+    sync_index: bool,
+    #[serde(default = "default_sheet_size")]
+    default_sheet_size: (usize, usize),
+    #[serde(default = "default_cell")]
+    default_cell: CellValue,
+    #[serde(default)]
+    tokenizer: Tokenizer,
+}
+
+impl From<SavedEditorState> for EditorState {
+    fn from(saved: SavedEditorState) -> Self {
+        let mut state = EditorState::new(saved.file_data);
+        state.history = saved.history;
+        state.redo_stack = saved.redo_stack;
+        state.sync_index = saved.sync_index;
+        state.default_sheet_size = saved.default_sheet_size;
+        state.default_cell = saved.default_cell;
+        state.tokenizer = saved.tokenizer;
+        state.update_flags();
+        if state.sync_index {
+            state.rebuild_all_indices();
+        }
+        state
+    }
+}
+
+/// 全局递增的版本号，重新加载文件后也不会与旧状态的版本号重复
+fn next_revision() -> u64 {
+    static REVISION: AtomicU64 = AtomicU64::new(0);
+    REVISION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+type OperationListener = Box<dyn Fn(OperationAction, &OperationResult) + Send + Sync>;
+
+static OPERATION_LISTENER: OnceLock<OperationListener> = OnceLock::new();
+
+/// 注册状态变更监听器，每次执行/撤销/重做后以增量结果调用（只能注册一次，重复注册会被忽略）
+/// 监听器在持有编辑器状态写锁时被调用，不能再访问全局状态
+pub fn set_operation_listener(listener: impl Fn(OperationAction, &OperationResult) + Send + Sync + 'static) {
+    let _ = OPERATION_LISTENER.set(Box::new(listener));
+}
+
+fn notify_operation(action: OperationAction, result: &OperationResult) {
+    if let Some(listener) = OPERATION_LISTENER.get() {
+        listener(action, result);
+    }
 }
 
 impl EditorState {
     pub fn new(file_data: FileData) -> Self {
         Self {
+            saved_hash: file_data.content_hash(),
             file_data,
             history: Vec::new(),
             redo_stack: Vec::new(),
             can_undo: false,
             can_redo: false,
+            sync_index: false,
+            default_sheet_size: DEFAULT_SHEET_SIZE,
+            default_cell: CellValue::Null,
+            revision: next_revision(),
+            tokenizer: Tokenizer::default(),
+            search_cursor: None,
         }
     }
 
@@ -35,9 +133,14 @@ impl EditorState {
                     if let Some(real_old) = sheet.rows.get(*row).and_then(|r| r.get(*col)) {
                         // 如果新值和旧值相同，不需要记录到 history
                         if real_old == new_value {
-                            // 返回结果但不记录到 history
-                            let result = operation.execute(&mut self.file_data);
+                            // 返回结果但不记录到 history，并在结果中标明，调用方据此判断是否产生了可撤销的修改
+                            let mut result = operation.execute(&mut self.file_data);
+                            if let OperationResult::SetCell { recorded, .. } = &mut result {
+                                *recorded = false;
+                            }
                             self.update_flags();
+                            self.revision = next_revision();
+                            notify_operation(OperationAction::Execute, &result);
                             return result;
                         }
                         // 只有当后端获取的旧值与前端传入的不同时，才更新 operation
@@ -53,25 +156,31 @@ impl EditorState {
                     }
                 }
             }
-            // AddColumn: 添加空列，需要补充列索引（只当 col_data 为空时）
-            Operation::AddColumn { sheet_index, col_index, col_data: _ } => {
-                // 如果 col_data 已有数据（撤销操作），保留原数据
-                if col_index.is_none() && *sheet_index < self.file_data.sheets.len() {
-                    // 正常添加列，补充列索引
-                    if let Some(sheet) = self.file_data.sheets.get(*sheet_index) {
-                        let col_count = sheet.rows.first().map(|r| r.len()).unwrap_or(0);
-                        if col_count > 0 {
-                            operation = Operation::AddColumn {
-                                sheet_index: *sheet_index,
-                                col_index: Some(col_count - 1),
-                                col_data: vec![],
-                            };
-                        }
-                    }
+            // AddColumn: 添加空列，需要补充列数据（只当 col_data 为空时）
+            // 新列追加到每一行的末尾（col_index 保持 None），不规则行中的已有单元格不会移动
+            Operation::AddColumn { sheet_index, col_index: None, col_data, row_lens } if col_data.is_empty() => {
+                if let Some(sheet) = self.file_data.sheets.get(*sheet_index) {
+                    operation = Operation::AddColumn {
+                        sheet_index: *sheet_index,
+                        col_index: None,
+                        col_data: vec![self.default_cell.clone(); sheet.rows.len()],
+                        row_lens: row_lens.clone(),
+                    };
+                }
+            }
+            // DeleteColumn: 保存删除前各行的长度，撤销时只恢复实际删除了单元格的行
+            Operation::DeleteColumn { sheet_index, col_index, col_data, row_lens } if row_lens.is_empty() => {
+                if let Some(sheet) = self.file_data.sheets.get(*sheet_index) {
+                    operation = Operation::DeleteColumn {
+                        sheet_index: *sheet_index,
+                        col_index: *col_index,
+                        col_data: col_data.clone(),
+                        row_lens: sheet.rows.iter().map(Vec::len).collect(),
+                    };
                 }
             }
             // AddRow: 添加空行，需要补充行数据（只当 row_data 为空时）
-            Operation::AddRow { sheet_index, row_index, row_data } => {
+            Operation::AddRow { sheet_index, row_index, row_data, row_height } => {
                 // 如果 row_data 已有数据（撤销操作），保留原数据
                 if row_data.is_empty() && *sheet_index < self.file_data.sheets.len() {
                     if let Some(sheet) = self.file_data.sheets.get(*sheet_index) {
@@ -79,7 +188,8 @@ impl EditorState {
                         operation = Operation::AddRow {
                             sheet_index: *sheet_index,
                             row_index: *row_index,
-                            row_data: vec![CellValue::Null; col_count],
+                            row_data: vec![self.default_cell.clone(); col_count],
+                            row_height: *row_height,
                         };
                     }
                 }
@@ -102,6 +212,8 @@ impl EditorState {
         self.history.push(operation);
         self.redo_stack.clear();
         self.update_flags();
+        self.revision = next_revision();
+        notify_operation(OperationAction::Execute, &result);
         result
     }
 
@@ -115,6 +227,8 @@ impl EditorState {
             self.redo_stack.push(redo_op);
 
             self.update_flags();
+            self.revision = next_revision();
+            notify_operation(OperationAction::Undo, &result);
             Some(result)
         } else {
             None
@@ -127,12 +241,38 @@ impl EditorState {
             let result = operation.execute(&mut self.file_data);
             self.history.push(operation);
             self.update_flags();
+            self.revision = next_revision();
+            notify_operation(OperationAction::Redo, &result);
             Some(result)
         } else {
             None
         }
     }
 
+    /// 同步构建所有 sheet 的索引（返回后搜索结果即为最新）
+    pub fn rebuild_all_indices(&mut self) {
+        for sheet in &mut self.file_data.sheets {
+            rebuild_sheet_index(sheet, self.tokenizer);
+        }
+    }
+
+    /// 修改分词方式：已有索引全部作废，首次搜索时按新方式构建（开启 sync_index 时立即重建）
+    /// 更新版本号，使正在后台构建的旧方式索引不会被采用
+    pub fn set_tokenizer(&mut self, tokenizer: Tokenizer) {
+        if self.tokenizer == tokenizer {
+            return;
+        }
+        self.tokenizer = tokenizer;
+        self.revision = next_revision();
+        if self.sync_index {
+            self.rebuild_all_indices();
+        } else {
+            for sheet in &mut self.file_data.sheets {
+                sheet.index = Arc::default();
+            }
+        }
+    }
+
     fn update_flags(&mut self) {
         self.can_undo = !self.history.is_empty();
         self.can_redo = !self.redo_stack.is_empty();
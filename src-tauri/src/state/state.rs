@@ -3,4 +3,7 @@
 pub struct EditorStateInfo {
     pub can_undo: bool,
     pub can_redo: bool,
+    pub sync_index: bool,
+    /// 添加行/列时新单元格的值
+    pub default_cell: crate::types::CellValue,
 }
@@ -1,13 +1,234 @@
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum CellValue {
     Null,
+    /// 精确的整数（如长 ID），避免转换为 f64 时丢失精度。必须位于 Number 之前，
+    /// 反序列化时整数优先匹配此变体。超出安全范围的整数序列化为 `{"int": "..."}`，
+    /// 与内容恰为数字文本的字符串单元格区分
+    Integer(#[serde(serialize_with = "serialize_integer", deserialize_with = "deserialize_integer")] i64),
     String(String),
     Number(f64),
     Boolean(bool),
+    /// 日期（YYYY-MM-DD），序列化为 `{"date": "..."}` 以便与字符串区分
+    Date {
+        date: String,
+    },
+    /// 公式单元格：表达式（以 "=" 开头）及文件中缓存的计算结果
+    Formula {
+        expr: String,
+        cached: Box<CellValue>,
+    },
+    /// 带显示文本的数字：xlsx 中设置了数字格式（货币、百分比、千分位等）的单元格，
+    /// display 为按格式显示的文本，value 仍是参与排序、搜索和导出的原始值
+    Formatted {
+        value: Box<CellValue>,
+        display: String,
+    },
+}
+
+/// 单元格值类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellValueType {
+    Null,
+    String,
+    Number,
+    Boolean,
+    Date,
+}
+
+impl CellValue {
+    /// 获取单元格值类型（公式单元格按缓存结果的类型）
+    pub fn value_type(&self) -> CellValueType {
+        match self.cached_value() {
+            CellValue::Null | CellValue::Formula { .. } | CellValue::Formatted { .. } => CellValueType::Null,
+            CellValue::String(_) => CellValueType::String,
+            CellValue::Integer(_) | CellValue::Number(_) => CellValueType::Number,
+            CellValue::Boolean(_) => CellValueType::Boolean,
+            CellValue::Date { .. } => CellValueType::Date,
+        }
+    }
+
+    /// 由 f64 构造数字单元格：i64 范围内的精确整数使用 Integer，其余使用 Number
+    pub fn number(n: f64) -> CellValue {
+        // i64::MAX 无法精确表示为 f64，上界使用 2^63（不含）
+        const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+        if n.fract() == 0.0 && (-LIMIT..LIMIT).contains(&n) {
+            CellValue::Integer(n as i64)
+        } else {
+            CellValue::Number(n)
+        }
+    }
+
+    /// 数字单元格的 f64 值（非数字返回 None）
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CellValue::Integer(i) => Some(*i as f64),
+            CellValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// 获取单元格的实际值（公式单元格返回其缓存结果，带格式的数字返回原始值）
+    pub fn cached_value(&self) -> &CellValue {
+        match self {
+            CellValue::Formula { cached, .. } => cached.cached_value(),
+            CellValue::Formatted { value, .. } => value.cached_value(),
+            other => other,
+        }
+    }
+
+    /// 单元格的文本形式（搜索、索引、显示、导出统一使用）
+    /// Null 为空字符串，数字使用 format_number 的规范形式，公式单元格按缓存结果
+    pub fn to_text(&self) -> Cow<'_, str> {
+        match self.cached_value() {
+            CellValue::Null | CellValue::Formula { .. } | CellValue::Formatted { .. } => Cow::Borrowed(""),
+            CellValue::String(s) => Cow::Borrowed(s),
+            CellValue::Integer(i) => Cow::Owned(i.to_string()),
+            CellValue::Number(n) => Cow::Owned(format_number(*n)),
+            CellValue::Boolean(b) => Cow::Borrowed(if *b { "true" } else { "false" }),
+            CellValue::Date { date } => Cow::Borrowed(date),
+        }
+    }
+}
+
+/// 可作为 HashMap/HashSet 键的单元格值（CellValue 含 f64，本身不满足 Eq/Hash）
+/// 相等规则与 CellValue 的 PartialEq 一致（类型不同即不等，如 Integer(1) 与 Number(1.0)、
+/// 数字 1 与字符串 "1"），浮点数例外：
+/// - 所有 NaN 视为同一个值（PartialEq 中 NaN 不等于自身）
+/// - -0.0 与 0.0 视为同一个值（与 PartialEq、format_number 一致）
+#[derive(Clone, Copy, Debug)]
+pub struct CellKey<'a>(pub &'a CellValue);
+
+impl CellKey<'_> {
+    /// 浮点数的规范位模式：NaN 统一为 f64::NAN，-0.0 统一为 0.0
+    fn float_bits(n: f64) -> u64 {
+        if n.is_nan() {
+            f64::NAN.to_bits()
+        } else if n == 0.0 {
+            0.0f64.to_bits()
+        } else {
+            n.to_bits()
+        }
+    }
+
+    fn values_eq(a: &CellValue, b: &CellValue) -> bool {
+        match (a, b) {
+            (CellValue::Number(x), CellValue::Number(y)) => Self::float_bits(*x) == Self::float_bits(*y),
+            (
+                CellValue::Formula { expr: e1, cached: c1 },
+                CellValue::Formula { expr: e2, cached: c2 },
+            ) => e1 == e2 && Self::values_eq(c1, c2),
+            (
+                CellValue::Formatted { value: v1, display: d1 },
+                CellValue::Formatted { value: v2, display: d2 },
+            ) => d1 == d2 && Self::values_eq(v1, v2),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl PartialEq for CellKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        Self::values_eq(self.0, other.0)
+    }
+}
+
+impl Eq for CellKey<'_> {}
+
+impl std::hash::Hash for CellKey<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self.0).hash(state);
+        match self.0 {
+            CellValue::Null => {}
+            CellValue::String(s) => s.hash(state),
+            CellValue::Integer(i) => i.hash(state),
+            CellValue::Number(n) => Self::float_bits(*n).hash(state),
+            CellValue::Boolean(b) => b.hash(state),
+            CellValue::Date { date } => date.hash(state),
+            CellValue::Formula { expr, cached } => {
+                expr.hash(state);
+                CellKey(cached).hash(state);
+            }
+            CellValue::Formatted { value, display } => {
+                CellKey(value).hash(state);
+                display.hash(state);
+            }
+        }
+    }
+}
+
+/// JavaScript 能精确表示的最大整数（Number.MAX_SAFE_INTEGER）
+pub const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// 整数对象形式的键（`{"int": "9007199254740993"}`）
+const INTEGER_KEY: &str = "int";
+
+/// 超出 JavaScript 安全整数范围的整数序列化为 `{"int": "..."}`（与日期的 `{"date": ...}` 类似），
+/// 避免前端解析时丢失精度；安全范围内的整数仍为 JSON 数字
+fn serialize_integer<S: serde::Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+
+    if value.unsigned_abs() <= MAX_SAFE_INTEGER as u64 {
+        serializer.serialize_i64(*value)
+    } else {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(INTEGER_KEY, &value.to_string())?;
+        map.end()
+    }
+}
+
+/// 反序列化 Integer：接受 i64 范围内的 JSON 整数，以及 serialize_integer 生成的 `{"int": "..."}`
+/// 字符串不会读为整数，数字文本（如 "9007199254740993"）仍是字符串单元格
+fn deserialize_integer<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    use serde::de::{Error, MapAccess, Unexpected, Visitor};
+
+    struct IntegerVisitor;
+
+    impl<'de> Visitor<'de> for IntegerVisitor {
+        type Value = i64;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an integer, or {\"int\": \"...\"} holding an integer beyond ±2^53")
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<i64, E> {
+            Ok(value)
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<i64, E> {
+            i64::try_from(value).map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<i64, A::Error> {
+            // 只接受恰好一个 "int" 键，其它对象（日期、公式等）交给后面的变体
+            if map.next_key::<String>()?.as_deref() != Some(INTEGER_KEY) {
+                return Err(A::Error::invalid_type(Unexpected::Map, &self));
+            }
+            let text: String = map.next_value()?;
+            if map.next_key::<String>()?.is_some() {
+                return Err(A::Error::invalid_type(Unexpected::Map, &self));
+            }
+            text.parse().map_err(|_| A::Error::invalid_value(Unexpected::Str(&text), &self))
+        }
+    }
+
+    deserializer.deserialize_any(IntegerVisitor)
+}
+
+/// 数字的规范文本形式：整数不带小数部分（1000.0 -> "1000"），小数使用最短精确表示，
+/// -0 视为 0。建索引和数字查询都使用这一形式，保证输入的数字能命中数字单元格
+pub fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        // 同时处理 -0.0
+        "0".to_string()
+    } else {
+        n.to_string()
+    }
 }
 
 /// 单元格位置
@@ -25,7 +246,29 @@ pub struct SearchResult {
     pub row: usize,
     pub col: usize,
     pub value: String,
+    /// 匹配单元格的原始值类型
+    pub value_type: CellValueType,
     pub cell_position: String,
+    /// 匹配部分在 value 中的起止位置（按字符计，不含 match_end），用于高亮
+    /// 短语模式为短语所在位置，整格和按词模式为整个 value
+    pub match_start: usize,
+    pub match_end: usize,
+}
+
+/// 查找栏的搜索游标：search_begin 时计算的匹配列表（之后不随编辑变化）及当前位置
+#[derive(Clone, Debug, Default)]
+pub struct SearchCursor {
+    pub results: Vec<SearchResult>,
+    /// 当前匹配的下标，尚未移动过时为 None
+    pub position: Option<usize>,
+}
+
+/// 每个 sheet 的搜索命中数
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SheetMatchCount {
+    pub sheet_index: usize,
+    pub sheet_name: String,
+    pub count: usize,
 }
 
 /// 搜索范围
@@ -36,10 +279,161 @@ pub enum SearchScope {
     AllSheets,
 }
 
+/// 搜索模式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchMode {
+    /// 整格匹配（不区分大小写）
+    #[default]
+    Exact,
+    /// 按词匹配：查询拆分为词，单元格需包含所有词（顺序不限）
+    Words,
+    /// 短语匹配：单元格需包含与查询完全一致的连续文本（不区分大小写），且首尾落在词边界上
+    Phrase,
+}
+
+/// 字符串单元格的换行风格
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NewlineStyle {
+    /// \n
+    Lf,
+    /// \r\n
+    Crlf,
+}
+
+impl NewlineStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NewlineStyle::Lf => "\n",
+            NewlineStyle::Crlf => "\r\n",
+        }
+    }
+}
+
+/// 列校验规则
+/// 除 NonEmpty 外，空单元格（Null）视为通过校验
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum ValidationRule {
+    /// 必须是数字
+    IsNumber,
+    /// 必须是布尔值
+    IsBoolean,
+    /// 单元格文本必须匹配正则表达式
+    Matches(String),
+    /// 不能为空
+    NonEmpty,
+}
+
+/// 列数据概况
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ColumnProfile {
+    /// 非空单元格数
+    pub filled: usize,
+    /// 空单元格数（Null、空字符串或不规则行中缺失的单元格）
+    pub empty: usize,
+    pub numeric: usize,
+    pub text: usize,
+    #[serde(rename = "bool")]
+    pub boolean: usize,
+    pub date: usize,
+}
+
+/// 列中的一个不重复值及其出现次数
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValueCount {
+    pub value: CellValue,
+    pub count: usize,
+}
+
+/// sheet 的行数统计
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RowCount {
+    pub total_rows: usize,
+    /// 所有单元格均为 Null 的行数
+    pub empty_rows: usize,
+}
+
+/// 工作簿校验发现的问题类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssueKind {
+    /// 行比最宽的行短
+    RaggedRow,
+    /// 数字为 NaN 或无穷大（xlsx 无法表示）
+    NonFiniteNumber,
+    /// 文本超过 xlsx 单元格的字符数上限
+    OversizedCell,
+    /// sheet 名称与前面的 sheet 重复（不区分大小写，与 Excel 一致）
+    DuplicateSheetName,
+    /// 数据超出 xlsx 的行数或列数上限
+    ExceedsXlsxLimit,
+}
+
+/// 工作簿校验发现的一个问题
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Issue {
+    pub sheet: usize,
+    /// 问题所在的单元格，整个 sheet 的问题（如重名）为 None
+    pub position: Option<CellPosition>,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+/// 聚合函数
+/// Sum/Avg/Min/Max 只统计数字，Count 统计所有非空值
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum AggregateFunc {
+    Sum,
+    Avg,
+    Count,
+    Min,
+    Max,
+}
+
+/// 整格索引的统计信息（用于排查大文件的索引内存占用）
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IndexStats {
+    /// 不同 token 的数量
+    pub token_count: usize,
+    /// 所有 token 记录的位置总数
+    pub total_postings: usize,
+    /// 估算的字节数：token 长度之和加上所有 CellPosition 的大小（不含 HashMap/Vec 的额外开销）
+    pub bytes_estimate: usize,
+    /// 索引是否已构建（未构建时其余字段均为 0）
+    pub built: bool,
+}
+
+/// 按词索引（按词、短语搜索）的分词方式
+/// - Punctuation：按空白和标点拆分（"a-b-c" -> a、b、c）
+/// - Whitespace：只按空白拆分（"N/A"、"a-b-c" 各为一个词）
+/// - WholeCell：整格（去掉首尾空白）作为一个词
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Tokenizer {
+    #[default]
+    Punctuation,
+    Whitespace,
+    WholeCell,
+}
+
 /// Sheet 索引（不序列化）
 #[derive(Clone, Debug, Default)]
 pub struct SheetIndex {
+    /// 整格文本（小写）-> 位置
     pub inverted_index: HashMap<String, Vec<CellPosition>>,
+    /// 单词（小写）-> 位置，每个单元格中的每个词只记录一次
+    pub word_index: HashMap<String, Vec<CellPosition>>,
+    /// 构建 word_index 时使用的分词方式（增量更新和查询都按此分词）
+    pub tokenizer: Tokenizer,
+    /// 是否已构建。索引在首次搜索时才构建，未构建时编辑操作不维护索引
+    pub built: bool,
+}
+
+/// 冻结窗格：冻结顶部 rows 行和左侧 cols 列
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FreezePanes {
+    pub rows: u32,
+    pub cols: u16,
 }
 
 /// 合并范围
@@ -51,14 +445,46 @@ pub struct MergeRange {
     pub end_col: u16,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SheetData {
     pub name: String,
     pub rows: Vec<Vec<CellValue>>,
     /// 合并范围
     pub merges: Vec<MergeRange>,
+    /// 是否可见（原文件中隐藏的 sheet 为 false，仍保留在原位置）
+    #[serde(default = "default_visible")]
+    pub visible: bool,
+    /// 第一行是否为表头（为 true 时排序、分组、查重等操作跳过第一行）
+    #[serde(default)]
+    pub has_header: bool,
+    /// 行高（磅），None 为默认行高；长度可短于行数，之后的行均为默认行高
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub row_heights: Vec<Option<f64>>,
+    /// 冻结窗格（保存为 xlsx 时应用），None 为不冻结
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freeze: Option<FreezePanes>,
+    /// 索引以 Arc 共享：搜索时克隆快照后即可释放锁，编辑时写时复制
     #[serde(skip)]
-    pub index: SheetIndex,
+    pub index: Arc<SheetIndex>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+impl Default for SheetData {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            rows: Vec::new(),
+            merges: Vec::new(),
+            visible: true,
+            has_header: false,
+            row_heights: Vec::new(),
+            freeze: None,
+            index: Arc::default(),
+        }
+    }
 }
 
 impl SheetData {
@@ -67,12 +493,352 @@ impl SheetData {
     pub fn is_empty(&self) -> bool {
         self.name.is_empty() && self.rows.is_empty()
     }
+
+    /// 数据行的起始行号（有表头时为 1）
+    pub fn data_start(&self) -> usize {
+        usize::from(self.has_header && !self.rows.is_empty())
+    }
+
+    /// 指定行的行高（None 为默认行高）
+    pub fn row_height(&self, row: usize) -> Option<f64> {
+        self.row_heights.get(row).copied().flatten()
+    }
+
+    /// 插入行时同步插入行高，使其后的行高随行下移
+    pub fn insert_row_height(&mut self, row: usize, height: Option<f64>) {
+        if row < self.row_heights.len() {
+            self.row_heights.insert(row, height);
+        } else if height.is_some() {
+            self.row_heights.resize(row, None);
+            self.row_heights.push(height);
+        }
+    }
+
+    /// 删除行时同步删除行高，使其后的行高随行上移
+    pub fn remove_row_height(&mut self, row: usize) {
+        if row < self.row_heights.len() {
+            self.row_heights.remove(row);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FileData {
     pub file_name: String,
     pub sheets: Vec<SheetData>,
+    /// 读取 CSV 时使用的分隔符（显式指定或自动检测），其它格式为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<char>,
+    /// 读取时的警告（如无法读取而被跳过的 sheet）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// 将写入的字节直接送入 Hasher，序列化时不生成中间字符串
+struct HashWriter<'a, H: std::hash::Hasher>(&'a mut H);
+
+impl<H: std::hash::Hasher> std::io::Write for HashWriter<'_, H> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl FileData {
+    /// 文件内容的哈希（基于序列化结果，不含索引），只在本进程内可比较
+    /// 用于判断编辑后是否又回到了加载/保存时的内容（如修改后撤销）
+    pub fn content_hash(&self) -> u64 {
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        // 写入 Hasher 不会失败，FileData 的序列化也不会失败
+        let _ = serde_json::to_writer(HashWriter(&mut hasher), self);
+        hasher.finish()
+    }
+}
+
+/// 紧凑编码的文件数据（get_file_data_compact 的返回值，直接借用编辑器状态序列化）
+/// 字段与 FileData 相同，只有每个 sheet 的 rows 使用游程编码：
+/// - rows 中连续 2 个及以上、宽度相同的全空行编码为 `{"empty_rows": n, "width": w}`
+/// - 其余行仍为数组，行内连续 2 个及以上的 Null 编码为 `{"nulls": n}`，单个 Null 仍为 null
+/// - 其余单元格与 CellValue 的序列化相同（公式单元格为含 expr/cached 的对象，可据此与上述对象区分）
+#[derive(Serialize)]
+pub struct CompactFileData<'a> {
+    pub file_name: &'a str,
+    pub sheets: Vec<CompactSheet<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<char>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub warnings: &'a [String],
+}
+
+#[derive(Serialize)]
+pub struct CompactSheet<'a> {
+    pub name: &'a str,
+    #[serde(serialize_with = "serialize_compact_rows")]
+    pub rows: &'a [Vec<CellValue>],
+    pub merges: &'a [MergeRange],
+    pub visible: bool,
+    pub has_header: bool,
+    #[serde(skip_serializing_if = "<[Option<f64>]>::is_empty")]
+    pub row_heights: &'a [Option<f64>],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze: Option<FreezePanes>,
+}
+
+impl<'a> From<&'a FileData> for CompactFileData<'a> {
+    fn from(file_data: &'a FileData) -> Self {
+        Self {
+            file_name: &file_data.file_name,
+            sheets: file_data
+                .sheets
+                .iter()
+                .map(|sheet| CompactSheet {
+                    name: &sheet.name,
+                    rows: &sheet.rows,
+                    merges: &sheet.merges,
+                    visible: sheet.visible,
+                    has_header: sheet.has_header,
+                    row_heights: &sheet.row_heights,
+                    freeze: sheet.freeze,
+                })
+                .collect(),
+            delimiter: file_data.delimiter,
+            warnings: &file_data.warnings,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EmptyRowRun {
+    empty_rows: usize,
+    width: usize,
+}
+
+#[derive(Serialize)]
+struct NullRun {
+    nulls: usize,
+}
+
+/// 一行内的单元格，连续的 Null 按游程编码
+struct CompactRow<'a>(&'a [CellValue]);
+
+impl Serialize for CompactRow<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut cells = self.0;
+        while let Some(first) = cells.first() {
+            let run = match first {
+                CellValue::Null => cells.iter().take_while(|c| matches!(c, CellValue::Null)).count(),
+                _ => 1,
+            };
+            if run >= 2 {
+                seq.serialize_element(&NullRun { nulls: run })?;
+            } else {
+                seq.serialize_element(first)?;
+            }
+            cells = &cells[run..];
+        }
+        seq.end()
+    }
+}
+
+fn is_empty_row(row: &[CellValue]) -> bool {
+    row.iter().all(|c| matches!(c, CellValue::Null))
+}
+
+fn serialize_compact_rows<S: serde::Serializer>(rows: &&[Vec<CellValue>], serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeSeq;
+
+    let mut seq = serializer.serialize_seq(None)?;
+    let mut rows = *rows;
+    while let Some(first) = rows.first() {
+        let run = if is_empty_row(first) {
+            rows.iter().take_while(|r| r.len() == first.len() && is_empty_row(r)).count()
+        } else {
+            1
+        };
+        if run >= 2 {
+            seq.serialize_element(&EmptyRowRun { empty_rows: run, width: first.len() })?;
+        } else {
+            seq.serialize_element(&CompactRow(first))?;
+        }
+        rows = &rows[run..];
+    }
+    seq.end()
+}
+
+/// 读取时单个字符串单元格的默认最大字符数（与 xlsx 的单元格上限一致）
+pub const DEFAULT_MAX_CELL_LEN: usize = 32_767;
+
+/// 读取选项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ReadOptions {
+    /// 读取后转置每个 sheet（用于按列存储记录的键值文件）
+    pub transpose: bool,
+    /// CSV 分隔符，None 时根据文件开头几行自动检测（, ; \t |）
+    pub delimiter: Option<char>,
+    /// CSV 中布尔值的表示方式（需与写入时的选项一致，才能读回布尔值）
+    pub csv_booleans: CsvBooleans,
+    /// 将 CSV 中形如日期的字段解析为日期单元格（默认关闭，避免把像数字的文本误判为日期）
+    pub parse_dates: bool,
+    /// 尝试的日期格式（chrono 格式串，如 "%d/%m/%Y"），为空时使用 ISO 等无歧义的默认格式
+    pub date_formats: Vec<String>,
+    /// 字符串单元格的最大字符数，超出部分截断并以省略号结尾（记录警告），None 表示不限制
+    pub max_cell_len: Option<usize>,
+    /// 显式指定文件格式（扩展名与实际内容不符时使用），None 时按扩展名推断
+    pub format: Option<FileFormat>,
+    /// CSV 各行字段数不一致时不报错，较短的行用 Null 补齐到最宽行的宽度
+    pub flexible: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            transpose: false,
+            delimiter: None,
+            csv_booleans: CsvBooleans::default(),
+            parse_dates: false,
+            date_formats: Vec::new(),
+            max_cell_len: Some(DEFAULT_MAX_CELL_LEN),
+            format: None,
+            flexible: false,
+        }
+    }
+}
+
+/// 写入选项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WriteOptions {
+    /// CSV 中布尔值的表示方式
+    pub csv_booleans: CsvBooleans,
+    /// 显式指定写入格式（忽略路径扩展名），None 时按扩展名推断
+    pub format: Option<FileFormat>,
+    /// 写入完成后计算文件的 SHA-256（用于归档校验）
+    pub checksum: bool,
+    /// CSV 分隔符（单字节 ASCII 字符），None 时按扩展名约定（.psv 为竖线，.txt 为制表符），默认逗号
+    pub delimiter: Option<char>,
+    /// 写入 xlsx 时清理 sheet 名称（移除非法字符、截断到 31 个字符、去重），改名记录在警告中；
+    /// 关闭时名称原样写入，非法名称会导致保存失败
+    pub sanitize_sheet_names: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            csv_booleans: CsvBooleans::default(),
+            format: None,
+            checksum: false,
+            delimiter: None,
+            sanitize_sheet_names: true,
+        }
+    }
+}
+
+/// CSV 中布尔值的表示方式
+/// - TrueFalse：写为 "true"/"false"；读取时 "true"/"false"（不区分大小写）为布尔值，"1"/"0" 为数字
+/// - OneZero：写为 "1"/"0"；读取时 "1"/"0" 也视为布尔值（整列数字 1/0 同样会被读为布尔值）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CsvBooleans {
+    #[default]
+    TrueFalse,
+    OneZero,
+}
+
+/// 文件格式（读写均按此分派；新增格式只需在这里登记扩展名和读写能力）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    /// .csv，以及按分隔符区分的 .psv（竖线）/ .txt（制表符）
+    Csv,
+    /// .xlsx / .xlsm
+    Xlsx,
+    Xls,
+    Ods,
+    /// Apple Numbers：只用于识别，目前无法读取（读取时提示用户导出为 xlsx/csv）
+    Numbers,
+}
+
+impl FileFormat {
+    /// 根据扩展名推断格式（不区分大小写）
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "csv" | "psv" | "txt" => Some(FileFormat::Csv),
+            "xlsx" | "xlsm" => Some(FileFormat::Xlsx),
+            "xls" => Some(FileFormat::Xls),
+            "ods" => Some(FileFormat::Ods),
+            "numbers" => Some(FileFormat::Numbers),
+            _ => None,
+        }
+    }
+
+    /// 根据文件路径或文件名的扩展名推断格式
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        path.extension().and_then(|e| e.to_str()).and_then(Self::from_extension)
+    }
+
+    /// 文本格式按扩展名约定的分隔符（.psv 为竖线，.txt 为制表符），其余为 None
+    pub fn default_delimiter(path: &std::path::Path) -> Option<char> {
+        match path.extension().and_then(|e| e.to_str())?.to_lowercase().as_str() {
+            "psv" => Some('|'),
+            "txt" => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// 是否支持读取
+    pub fn can_read(self) -> bool {
+        !matches!(self, FileFormat::Numbers)
+    }
+
+    /// 是否支持写入（xlsxwriter 只能写 xlsx，xls/ods 仅可读）
+    pub fn can_write(self) -> bool {
+        matches!(self, FileFormat::Csv | FileFormat::Xlsx)
+    }
+
+    /// 按显式指定的格式或路径扩展名确定格式
+    pub fn resolve(explicit: Option<Self>, path: &std::path::Path) -> Option<Self> {
+        explicit.or_else(|| Self::from_path(path))
+    }
+}
+
+/// 保存结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveResult {
+    /// 实际写入的路径（格式降级时可能与请求的路径不同）
+    pub path: String,
+    /// 保存过程中的提示信息（如 .xlsm 降级为 .xlsx）
+    pub warnings: Vec<String>,
+    /// 写入文件的 SHA-256（小写十六进制），仅在 WriteOptions.checksum 开启时计算
+    pub sha256: Option<String>,
+}
+
+/// 批量删除行（去重、删除空行）的结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RowRemoval {
+    /// 被删除（预览时为将被删除）的行号，升序
+    pub rows: Vec<usize>,
+    pub count: usize,
+    /// 删除操作的结果（DeleteRow 的 Group，可一次撤销）；预览或没有可删除的行时为 None
+    pub result: Option<OperationResult>,
+}
+
+/// 批量导入结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportResult {
+    /// 新增 sheet 的操作结果（Group，可一次撤销），没有导入任何文件时为 None
+    pub result: Option<OperationResult>,
+    /// 被跳过的文件及原因，以及读取时的其它警告
+    pub warnings: Vec<String>,
 }
 
 /// 单元格变化
@@ -88,6 +854,9 @@ pub struct CellChange {
 pub struct RowChange {
     pub index: usize,
     pub values: Vec<CellValue>,
+    /// 插入行的行高（撤销删除时恢复），默认行高时省略
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
 }
 
 /// 列变化
@@ -103,6 +872,28 @@ pub struct SortState {
     pub ascending: bool,
 }
 
+/// 状态变更的来源
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationAction {
+    Execute,
+    Undo,
+    Redo,
+}
+
+/// "operation-applied" 事件的负载
+#[derive(Serialize, Clone, Debug)]
+pub struct OperationApplied<'a> {
+    pub action: OperationAction,
+    /// 操作类型（与 result 的 type 字段一致），便于监听方过滤
+    pub kind: &'static str,
+    pub result: &'a OperationResult,
+}
+
+fn default_recorded() -> bool {
+    true
+}
+
 /// 操作结果（增量数据）
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "type", content = "data")]
@@ -111,6 +902,9 @@ pub enum OperationResult {
     SetCell {
         sheet_index: usize,
         cell: CellChange,
+        /// 是否记录到撤销历史（新值与当前值相同时为 false，此时没有可撤销的修改）
+        #[serde(default = "default_recorded")]
+        recorded: bool,
     },
     /// 添加行
     AddRow {
@@ -128,22 +922,40 @@ pub enum OperationResult {
         column: ColumnChange,
         /// 添加的列数据（用于撤销时恢复）
         col_data: Vec<CellValue>,
+        /// 该列存在时各行的长度，只在 column.index 小于记录长度的行中插入；
+        /// 为空时插入到长度不小于 column.index 的行
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        row_lens: Vec<usize>,
+        /// 为 true 时追加到每一行的末尾（不规则行各自追加），column.index 仅供参考
+        #[serde(default)]
+        at_end: bool,
     },
     /// 删除列
     DeleteColumn {
         sheet_index: usize,
         column_index: usize,
+        /// 被删除的列数据（每行一个值，行长度不足时为 Null）
+        col_data: Vec<CellValue>,
+        /// 为 true 时删除每一行的最后一个单元格（撤销追加列），column_index 仅供参考
+        #[serde(default)]
+        at_end: bool,
     },
     /// 添加 Sheet
     AddSheet {
+        /// 新 sheet 插入的位置
         sheet_index: usize,
+        /// 建议切换到的 sheet（即新 sheet）
+        active_sheet_index: usize,
         name: String,
         /// 完整的 sheet 数据（用于撤销时恢复）
         sheet_data: SheetData,
     },
     /// 删除 Sheet
     DeleteSheet {
+        /// 被删除的 sheet 原来的位置
         sheet_index: usize,
+        /// 删除后建议切换到的 sheet（原位置，若删除的是最后一个则为新的最后一个）
+        active_sheet_index: usize,
         /// 被删除的 sheet 数据（用于撤销时恢复）
         sheet_data: SheetData,
     },
@@ -153,4 +965,149 @@ pub enum OperationResult {
         sheet_data: SheetData,
         sort_state: Option<SortState>,
     },
+    /// 调整 sheet 大小（或撤销调整），rows 和 row_heights 为操作后的全部行数据和行高
+    ResizeSheet {
+        sheet_index: usize,
+        rows: Vec<Vec<CellValue>>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        row_heights: Vec<Option<f64>>,
+    },
+    /// 移动行（to 为移动后的位置，行高随行移动）
+    MoveRow {
+        sheet_index: usize,
+        from: usize,
+        to: usize,
+    },
+    /// 移动列，rows 为操作后的全部行数据（不规则行的长度可能随之变化）
+    MoveColumn {
+        sheet_index: usize,
+        from: usize,
+        to: usize,
+        rows: Vec<Vec<CellValue>>,
+    },
+    /// 移动 sheet
+    MoveSheet {
+        from: usize,
+        to: usize,
+        /// 建议切换到的 sheet（即被移动的 sheet 的新位置）
+        active_sheet_index: usize,
+    },
+    /// 操作组（按执行顺序排列的各子操作结果）
+    Group {
+        results: Vec<OperationResult>,
+    },
+}
+
+impl OperationResult {
+    /// 操作类型名称（与序列化后的 type 字段一致）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OperationResult::SetCell { .. } => "SetCell",
+            OperationResult::AddRow { .. } => "AddRow",
+            OperationResult::DeleteRow { .. } => "DeleteRow",
+            OperationResult::AddColumn { .. } => "AddColumn",
+            OperationResult::DeleteColumn { .. } => "DeleteColumn",
+            OperationResult::AddSheet { .. } => "AddSheet",
+            OperationResult::DeleteSheet { .. } => "DeleteSheet",
+            OperationResult::SortColumn { .. } => "SortColumn",
+            OperationResult::ResizeSheet { .. } => "ResizeSheet",
+            OperationResult::MoveRow { .. } => "MoveRow",
+            OperationResult::MoveColumn { .. } => "MoveColumn",
+            OperationResult::MoveSheet { .. } => "MoveSheet",
+            OperationResult::Group { .. } => "Group",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::editor_state::{EditorState, Operation};
+
+    const LARGE: i64 = 1_234_567_890_123_456_789;
+
+    fn round_trip(value: &CellValue) -> CellValue {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn large_integers_round_trip_through_json() {
+        for value in [LARGE, -LARGE, i64::MAX, i64::MIN, MAX_SAFE_INTEGER + 1] {
+            let cell = CellValue::Integer(value);
+            assert_eq!(serde_json::to_string(&cell).unwrap(), format!("{{\"int\":\"{value}\"}}"));
+            assert_eq!(round_trip(&cell), cell);
+        }
+        for value in [0, -1, MAX_SAFE_INTEGER, -MAX_SAFE_INTEGER] {
+            let cell = CellValue::Integer(value);
+            assert_eq!(serde_json::to_string(&cell).unwrap(), value.to_string());
+            assert_eq!(round_trip(&cell), cell);
+        }
+    }
+
+    #[test]
+    fn other_values_round_trip_through_json() {
+        let cells = [
+            CellValue::Null,
+            CellValue::String("007".to_string()),
+            CellValue::String("12345".to_string()),
+            CellValue::String("+1234567890123456789".to_string()),
+            CellValue::String("99999999999999999999".to_string()),
+            CellValue::Number(1.5),
+            CellValue::Number(1e300),
+            CellValue::Boolean(true),
+            CellValue::Date { date: "2024-01-31".to_string() },
+            CellValue::Formula { expr: "=A1".to_string(), cached: Box::new(CellValue::Integer(LARGE)) },
+        ];
+        for cell in cells {
+            assert_eq!(round_trip(&cell), cell);
+        }
+        // 超出 i64 范围的 JSON 整数按浮点数读取
+        assert_eq!(serde_json::from_str::<CellValue>("18446744073709551615").unwrap(), CellValue::Number(u64::MAX as f64));
+    }
+
+    #[test]
+    fn integer_text_stays_a_string() {
+        for text in [LARGE.to_string(), (-LARGE).to_string(), i64::MAX.to_string()] {
+            let cell = CellValue::String(text);
+            assert_eq!(round_trip(&cell), cell);
+        }
+        // 多余的键或非整数内容不是整数对象
+        assert!(serde_json::from_str::<CellValue>(r#"{"int": "12", "x": 1}"#).is_err());
+        assert!(serde_json::from_str::<CellValue>(r#"{"int": "1.5"}"#).is_err());
+        assert_eq!(serde_json::from_str::<CellValue>(r#"{"int": "12"}"#).unwrap(), CellValue::Integer(12));
+    }
+
+    #[test]
+    fn large_integers_survive_file_data_and_editor_state() {
+        let file_data = FileData {
+            file_name: "ids.csv".to_string(),
+            sheets: vec![SheetData {
+                name: "Sheet1".to_string(),
+                rows: vec![vec![CellValue::Integer(LARGE), CellValue::Integer(-LARGE)]],
+                ..SheetData::default()
+            }],
+            delimiter: None,
+            warnings: Vec::new(),
+        };
+        let read: FileData = serde_json::from_str(&serde_json::to_string(&file_data).unwrap()).unwrap();
+        assert_eq!(read.sheets[0].rows, file_data.sheets[0].rows);
+
+        let mut state = EditorState::new(file_data);
+        state.execute(Operation::SetCell {
+            sheet_index: 0,
+            row: 0,
+            col: 0,
+            old_value: CellValue::Integer(LARGE),
+            new_value: CellValue::Integer(LARGE + 1),
+        });
+        let read: EditorState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        assert_eq!(read.file_data.sheets[0].rows[0][0], CellValue::Integer(LARGE + 1));
+        match &read.history[..] {
+            [Operation::SetCell { old_value, new_value, .. }] => {
+                assert_eq!(old_value, &CellValue::Integer(LARGE));
+                assert_eq!(new_value, &CellValue::Integer(LARGE + 1));
+            }
+            history => panic!("unexpected history: {history:?}"),
+        }
+    }
 }
@@ -1,4 +1,26 @@
-export type CellValue = string | number | boolean | null;
+// 公式单元格：expr 为表达式（如 "=SUM(A1:A3)"），cached 为文件中缓存的计算结果
+export interface FormulaCell {
+  expr: string;
+  cached: CellValue;
+}
+
+// 日期单元格（YYYY-MM-DD），以对象形式传输以便与字符串区分
+export interface DateCell {
+  date: string;
+}
+
+// 带数字格式的单元格（来自 xlsx）：display 为按格式显示的文本，value 为原始值
+export interface FormattedCell {
+  value: CellValue;
+  display: string;
+}
+
+// 超出 Number.MAX_SAFE_INTEGER 的整数以对象形式传输（int 为十进制文本），避免精度丢失且与字符串区分
+export interface IntegerCell {
+  int: string;
+}
+
+export type CellValue = string | number | boolean | null | DateCell | FormulaCell | FormattedCell | IntegerCell;
 
 export interface MergeRange {
   start_row: number;
@@ -11,11 +33,83 @@ export interface SheetData {
   name: string;
   rows: CellValue[][];
   merges: MergeRange[];
+  // 原文件中隐藏的 sheet 为 false
+  visible: boolean;
+  // 第一行是否为表头（排序、分组等操作会固定表头）
+  has_header: boolean;
+  // 行高（磅），null 为默认行高；可短于行数，全部为默认行高时省略
+  row_heights?: (number | null)[];
+  // 冻结顶部 rows 行和左侧 cols 列（保存为 xlsx 时应用）
+  freeze?: FreezePanes;
+}
+
+export interface FreezePanes {
+  rows: number;
+  cols: number;
 }
 
 export interface FileData {
   file_name: string;
   sheets: SheetData[];
+  // 读取 CSV 时使用的分隔符（显式指定或自动检测）
+  delimiter?: string;
+  // 读取时的警告（如无法读取而被跳过的 sheet）
+  warnings?: string[];
+}
+
+// get_file_data_compact 的返回格式：rows 中的空值按游程编码（见 decodeCompactFileData）
+export type CompactCell = CellValue | { nulls: number };
+export type CompactRow = CompactCell[] | { empty_rows: number; width: number };
+
+export interface CompactSheetData extends Omit<SheetData, 'rows'> {
+  rows: CompactRow[];
+}
+
+export interface CompactFileData extends Omit<FileData, 'sheets'> {
+  sheets: CompactSheetData[];
+}
+
+// import_directory 的返回值：result 为新增 sheet 的 Group 结果（未导入任何文件时为 null）
+export interface ImportResult {
+  result: OperationResult | null;
+  warnings: string[];
+}
+
+export interface SaveResult {
+  path: string;
+  warnings: string[];
+  // 仅在保存选项 checksum 为 true 时返回
+  sha256: string | null;
+}
+
+// 批量删除行的结果，预览时 result 为 null
+export interface RowRemoval {
+  rows: number[];
+  count: number;
+  result: OperationResult | null;
+}
+
+// 列中的不重复值及出现次数
+export interface ValueCount {
+  value: CellValue;
+  count: number;
+}
+
+// 工作簿校验发现的问题类型
+export type IssueKind = 'RaggedRow' | 'NonFiniteNumber' | 'OversizedCell' | 'DuplicateSheetName' | 'ExceedsXlsxLimit';
+
+// 工作簿校验发现的一个问题，position 为 null 表示整个 sheet 的问题
+export interface Issue {
+  sheet: number;
+  position: { row: number; col: number } | null;
+  kind: IssueKind;
+  message: string;
+}
+
+// sheet 的行数统计，empty_rows 为所有单元格均为 null 的行数
+export interface RowCount {
+  total_rows: number;
+  empty_rows: number;
 }
 
 export interface CellChange {
@@ -27,6 +121,8 @@ export interface CellChange {
 export interface RowChange {
   index: number;
   values: CellValue[];
+  // 插入行的行高（撤销删除时恢复），默认行高时省略
+  height?: number;
 }
 
 export interface ColumnChange {
@@ -40,14 +136,26 @@ export interface SortState {
 
 // Rust 使用 #[serde(tag = "type", content = "data")]，所以格式是 { type: 'SetCell', data: {...} }
 export type OperationResult =
-  | { type: 'SetCell'; data: { sheet_index: number; cell: CellChange } }
+  | { type: 'SetCell'; data: { sheet_index: number; cell: CellChange; recorded: boolean } }
   | { type: 'AddRow'; data: { sheet_index: number; row: RowChange } }
   | { type: 'DeleteRow'; data: { sheet_index: number; row_index: number } }
-  | { type: 'AddColumn'; data: { sheet_index: number; column: ColumnChange; col_data: CellValue[] } }
-  | { type: 'DeleteColumn'; data: { sheet_index: number; column_index: number } }
-  | { type: 'AddSheet'; data: { sheet_index: number; name: string; sheet_data: SheetData } }
-  | { type: 'DeleteSheet'; data: { sheet_index: number; sheet_data: SheetData } }
-  | { type: 'SortColumn'; data: { sheet_index: number; sheet_data: SheetData; sort_state: SortState | null } };
+  | { type: 'AddColumn'; data: { sheet_index: number; column: ColumnChange; col_data: CellValue[]; row_lens?: number[]; at_end?: boolean } }
+  | { type: 'DeleteColumn'; data: { sheet_index: number; column_index: number; col_data: CellValue[]; at_end?: boolean } }
+  | { type: 'AddSheet'; data: { sheet_index: number; active_sheet_index: number; name: string; sheet_data: SheetData } }
+  | { type: 'DeleteSheet'; data: { sheet_index: number; active_sheet_index: number; sheet_data: SheetData } }
+  | { type: 'SortColumn'; data: { sheet_index: number; sheet_data: SheetData; sort_state: SortState | null } }
+  | { type: 'ResizeSheet'; data: { sheet_index: number; rows: CellValue[][]; row_heights?: (number | null)[] } }
+  | { type: 'MoveRow'; data: { sheet_index: number; from: number; to: number } }
+  | { type: 'MoveColumn'; data: { sheet_index: number; from: number; to: number; rows: CellValue[][] } }
+  | { type: 'MoveSheet'; data: { from: number; to: number; active_sheet_index: number } }
+  | { type: 'Group'; data: { results: OperationResult[] } };
+
+// "operation-applied" 事件负载：每次执行/撤销/重做后发出，kind 与 result.type 一致
+export interface OperationApplied {
+  action: 'execute' | 'undo' | 'redo';
+  kind: OperationResult['type'];
+  result: OperationResult;
+}
 
 export interface SearchResult {
   sheet_index: number;
@@ -55,7 +163,41 @@ export interface SearchResult {
   row: number;
   col: number;
   value: string;
+  value_type: CellValueType;
   cell_position: string;
+  // 匹配部分在 value 中的字符位置 [match_start, match_end)
+  match_start: number;
+  match_end: number;
+}
+
+export type CellValueType = 'Null' | 'String' | 'Number' | 'Boolean' | 'Date';
+
+export interface SheetMatchCount {
+  sheet_index: number;
+  sheet_name: string;
+  count: number;
+}
+
+export interface IndexStats {
+  token_count: number;
+  total_postings: number;
+  // token 长度之和加上位置大小的估算值（字节）
+  bytes_estimate: number;
+  // 索引在首次搜索时才构建，未构建时其余字段为 0
+  built: boolean;
 }
 
 export type SearchScope = 'currentSheet' | 'allSheets';
+export type FileFormat = 'csv' | 'xlsx' | 'xls' | 'ods' | 'numbers';
+export type SearchMode = 'exact' | 'words' | 'phrase';
+// CSV 布尔值的读写形式：true/false 或 1/0
+export type CsvBooleans = 'trueFalse' | 'oneZero';
+export type NewlineStyle = 'lf' | 'crlf';
+// 按词索引的分词方式：按空白和标点 / 只按空白 / 整格
+export type Tokenizer = 'punctuation' | 'whitespace' | 'wholeCell';
+
+// 后端错误：{ code: 'UNSUPPORTED_FORMAT', message: '...' }
+export interface AppError {
+  code: 'READ_ERROR' | 'WRITE_ERROR' | 'UNSUPPORTED_FORMAT' | 'INTERNAL' | 'OUT_OF_RANGE' | 'INVALID_SHEET_NAME' | 'INVALID_REFERENCE' | 'INVALID_PATTERN';
+  message: string;
+}
@@ -0,0 +1,38 @@
+import type { CellValue, CompactCell, CompactFileData, CompactRow, FileData } from "@/types";
+
+function isNullRun(cell: CompactCell): cell is { nulls: number } {
+  return typeof cell === "object" && cell !== null && "nulls" in cell;
+}
+
+function decodeRow(cells: CompactCell[]): CellValue[] {
+  const row: CellValue[] = [];
+  for (const cell of cells) {
+    if (isNullRun(cell)) {
+      for (let i = 0; i < cell.nulls; i++) row.push(null);
+    } else {
+      row.push(cell);
+    }
+  }
+  return row;
+}
+
+function decodeRows(rows: CompactRow[]): CellValue[][] {
+  const decoded: CellValue[][] = [];
+  for (const row of rows) {
+    if (Array.isArray(row)) {
+      decoded.push(decodeRow(row));
+    } else {
+      // 连续的全空行：每行单独分配，避免共享同一个数组
+      for (let i = 0; i < row.empty_rows; i++) decoded.push(Array(row.width).fill(null));
+    }
+  }
+  return decoded;
+}
+
+// 将 get_file_data_compact 返回的游程编码数据还原为 FileData
+export function decodeCompactFileData(data: CompactFileData): FileData {
+  return {
+    ...data,
+    sheets: data.sheets.map((sheet) => ({ ...sheet, rows: decodeRows(sheet.rows) })),
+  };
+}